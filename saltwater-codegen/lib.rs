@@ -205,7 +205,9 @@ impl<B: Backend> Compiler<B> {
                 let addr = builder.ins().stack_addr(Type::ptr_type(), stack_slot, 0);
                 builder.ins().store(MemFlags::new(), val.ir_val, addr, 0);
             }
-            Initializer::InitializerList(_) => unimplemented!("aggregate dynamic initialization"),
+            Initializer::InitializerList(_) | Initializer::Union(..) => {
+                unimplemented!("aggregate dynamic initialization")
+            }
             Initializer::FunctionBody(_) => unreachable!("functions can't be stored on the stack"),
         }
         Ok(())
@@ -269,14 +269,7 @@ impl<B: Backend> Compiler<B> {
                     assert_eq!(initializers.len(), 1);
                     self.init_symbol(ctx, buf, offset, initializers.remove(0), ctype, location)
                 }
-                Type::Union(struct_type) => self.init_symbol(
-                    ctx,
-                    buf,
-                    offset,
-                    initializers.remove(0),
-                    &struct_type.members().first().unwrap().ctype,
-                    location,
-                ),
+                Type::Union(_) => unreachable!("union initializers should be Initializer::Union"),
                 Type::Struct(struct_ref) => {
                     let mut current_offset = 0;
                     for (member, init) in struct_ref.members().iter().zip(initializers.into_iter())
@@ -301,6 +294,21 @@ impl<B: Backend> Compiler<B> {
                 Type::Void => unreachable!("initializer for void type"),
                 _ => unreachable!("scalar types should have been handled"),
             },
+            Initializer::Union(member, init) => match ctype {
+                // every member of a union starts at offset 0
+                Type::Union(struct_type) => {
+                    let members = struct_type.members();
+                    let member_type = &members[member].ctype;
+                    let size: usize = member_type
+                        .sizeof()
+                        .map_err(|err| CompileError::semantic(location.with(err.to_string())))?
+                        .try_into()
+                        .expect("cannot initialize union larger than host address space");
+                    let buf_slice = &mut buf[..size];
+                    self.init_symbol(ctx, buf_slice, offset, *init, member_type, location)
+                }
+                _ => unreachable!("only unions have union initializers"),
+            },
             Initializer::Scalar(expr) => self.init_expr(ctx, buf, offset, *expr),
             Initializer::FunctionBody(_) => {
                 panic!("function definitions should go through compile_function, not store_static")
@@ -1,6 +1,6 @@
 use super::PureAnalyzer;
 use crate::arch;
-use crate::data::{hir::*, lex::ComparisonToken, *};
+use crate::data::{error::Warning, hir::*, lex::ComparisonToken, *};
 use crate::intern::InternedStr;
 
 impl PureAnalyzer {
@@ -11,9 +11,22 @@ impl PureAnalyzer {
         let _guard2 = self.recursion_check();
         match expr.data {
             // 1 | "str" | 'a'
-            Literal(lit) => literal(lit, expr.location),
+            Literal(lit) => {
+                let mut lit = literal(lit, expr.location);
+                // string literals are arrays of plain `char`, whether or not that's signed
+                if let Type::Array(inner, _) = &mut lit.ctype {
+                    **inner = Type::Char(self.char_is_signed);
+                }
+                lit
+            }
             // x
             Id(id) => self.parse_id(id, expr.location),
+            // we've already reported this error
+            Error => {
+                let mut pretend_zero = Expr::zero(expr.location);
+                pretend_zero.ctype = Type::Error;
+                pretend_zero
+            }
             // (int)x
             Cast(ctype, inner) => {
                 let ctype = self.parse_typename(ctype, expr.location);
@@ -146,6 +159,18 @@ impl PureAnalyzer {
                 let inner = self.expr(*inner);
                 self.align(inner.ctype, expr.location)
             }
+            // GNU extension: `1.0i`
+            Imaginary(_) => {
+                let err = if self.gnu_extensions {
+                    SemanticError::ComplexNotSupported
+                } else {
+                    SemanticError::ImaginaryConstant
+                };
+                self.err(err, expr.location);
+                let mut zero = Expr::zero(expr.location);
+                zero.ctype = Type::Error;
+                zero
+            }
             SizeofType(type_name) => {
                 let ctype = self.parse_typename(type_name, expr.location);
                 self.sizeof(ctype, expr.location)
@@ -154,6 +179,8 @@ impl PureAnalyzer {
                 let inner = self.expr(*inner);
                 self.sizeof(inner.ctype, expr.location)
             }
+            // __builtin_types_compatible_p(int, unsigned)
+            TypesCompatible(left, right) => self.types_compatible(*left, *right, expr.location),
             BitwiseNot(inner) => self.bitwise_not(*inner),
             UnaryPlus(inner) => self.unary_add(*inner, true, expr.location),
             Negate(inner) => self.unary_add(*inner, false, expr.location),
@@ -185,6 +212,14 @@ impl PureAnalyzer {
             Ternary(condition, then, otherwise) => {
                 self.ternary(*condition, *then, *otherwise, expr.location)
             }
+            // __builtin_choose_expr(1, a, b)
+            ChooseExpr(condition, then, otherwise) => {
+                self.choose_expr(*condition, *then, *otherwise, expr.location)
+            }
+            // _Generic(x, int: 1, default: 0)
+            Generic(controlling, associations) => {
+                self.generic_selection(*controlling, associations, expr.location)
+            }
         }
     }
     // only meant for use with `expr`
@@ -505,6 +540,12 @@ impl PureAnalyzer {
     // `func(args)`
     // 6.5.2.2 Function calls
     fn func_call(&mut self, func: ast::Expr, args: Vec<ast::Expr>) -> Expr {
+        // `f()` where `f` was never declared
+        if let ast::ExprType::Id(name) = func.data {
+            if self.scope.get(&name).is_none() {
+                self.implicit_function_declaration(name, func.location);
+            }
+        }
         let mut func = self.expr(func);
         // if fp is a function pointer, fp() desugars to (*fp)()
         match &func.ctype {
@@ -561,6 +602,29 @@ impl PureAnalyzer {
             expr: ExprType::FuncCall(Box::new(func), promoted_args),
         }
     }
+    // C89 3.3.2.2: an undeclared function is implicitly declared as `extern int f();`
+    // in the innermost block. This was removed in C99.
+    fn implicit_function_declaration(&mut self, name: InternedStr, location: Location) {
+        if self.c89 {
+            self.warn(Warning::ImplicitFunctionDeclaration(name), location);
+        } else {
+            self.err(SemanticError::ImplicitFunctionDeclaration(name), location);
+        }
+        let implicit = Variable {
+            id: name,
+            ctype: Type::Function(types::FunctionType {
+                return_type: Box::new(Type::Int(true)),
+                params: Vec::new(),
+                varargs: false,
+            }),
+            qualifiers: Qualifiers::default(),
+            storage_class: StorageClass::Extern,
+        };
+        let symbol = self.declare(implicit, false, location);
+        // the backend still has to see the declaration, see `decl_side_channel`
+        let decl = Declaration { symbol, init: None };
+        self.decl_side_channel.push(Locatable::new(decl, location));
+    }
     /// 'default promotions' from 6.5.2.2p6
     fn default_promote(&mut self, expr: Expr) -> Expr {
         let expr = expr.rval();
@@ -688,6 +752,115 @@ impl PureAnalyzer {
         });
         literal(LiteralValue::UnsignedInt(align), location)
     }
+    // GNU extension: returns 1 if the types are compatible and 0 otherwise.
+    // Top-level qualifiers are ignored: those of objects were never part of the `Type`,
+    // and those of pointers are removed here. `int *const` is compatible with `int *`.
+    fn types_compatible(
+        &mut self,
+        left: ast::TypeName,
+        right: ast::TypeName,
+        location: Location,
+    ) -> Expr {
+        if !self.gnu_extensions {
+            let builtin = SemanticError::GnuBuiltin("__builtin_types_compatible_p");
+            self.err(builtin, location);
+        }
+        // qualifiers are expected to be ignored here,
+        // so use `parse_type` instead of `parse_typename` to avoid a warning
+        let left = self.parse_type(left.specifiers, left.declarator.decl, location);
+        let right = self.parse_type(right.specifiers, right.declarator.decl, location);
+        let compatible = compatible_types(&unqualified(left.ctype), &unqualified(right.ctype));
+        Expr {
+            ctype: Type::Int(true),
+            ..literal(LiteralValue::Int(compatible.into()), location)
+        }
+    }
+    // GNU extension: like `?:`, but `condition` must be a constant and only the chosen branch is used.
+    // The result has the type of the chosen branch, without any conversions.
+    fn choose_expr(
+        &mut self,
+        condition: ast::Expr,
+        then: ast::Expr,
+        otherwise: ast::Expr,
+        location: Location,
+    ) -> Expr {
+        if !self.gnu_extensions {
+            self.err(SemanticError::GnuBuiltin("__builtin_choose_expr"), location);
+        }
+        let choose_then = match self.expr(condition).const_fold() {
+            Ok(folded) => match folded.into_literal() {
+                Ok(literal) => !literal.is_zero(),
+                Err(runtime) => {
+                    self.err(SemanticError::NotConstant(runtime), location);
+                    true
+                }
+            },
+            Err(err) => {
+                self.error_handler.push_back(err);
+                true
+            }
+        };
+        let mut chosen = self.expr(if choose_then { then } else { otherwise });
+        chosen.location = location;
+        chosen
+    }
+    // 6.5.1.1 Generic selection
+    // Only the chosen association is analyzed, and the controlling expression is never evaluated.
+    fn generic_selection(
+        &mut self,
+        controlling: ast::Expr,
+        associations: Vec<ast::GenericAssociation>,
+        location: Location,
+    ) -> Expr {
+        // p2: "The type of the controlling expression is the type of the expression
+        // as if it had undergone an lvalue conversion"
+        // lvalue conversion also drops qualifiers, which `rval()` leaves on function pointers
+        let ctype = unqualified(self.expr(controlling).rval().ctype);
+        let mut seen = Vec::new();
+        let mut default = None;
+        let mut chosen = None;
+        for assoc in associations {
+            let assoc_type = match assoc.type_name {
+                Some(type_name) => {
+                    let decl = type_name.declarator.decl;
+                    Some(self.parse_type(type_name.specifiers, decl, location).ctype)
+                }
+                None => None,
+            };
+            // p2: "No two generic associations in the same generic selection
+            // shall specify compatible types."
+            let duplicate = seen.iter().any(|seen| match (seen, &assoc_type) {
+                (Some(seen), Some(assoc)) => compatible_types(seen, assoc),
+                (seen, assoc) => seen.is_none() && assoc.is_none(),
+            });
+            if duplicate {
+                let err = SemanticError::DuplicateGenericAssociation(assoc_type);
+                self.err(err, assoc.expr.location);
+                continue;
+            }
+            match &assoc_type {
+                Some(t) if compatible_types(t, &ctype) => chosen = Some(assoc.expr),
+                None => default = Some(assoc.expr),
+                _ => {}
+            }
+            seen.push(assoc_type);
+        }
+        match chosen.or(default) {
+            Some(expr) => {
+                let mut chosen = self.expr(expr);
+                chosen.location = location;
+                chosen
+            }
+            None => {
+                if ctype != Type::Error {
+                    self.err(SemanticError::NoGenericAssociation(ctype), location);
+                }
+                let mut zero = Expr::zero(location);
+                zero.ctype = Type::Error;
+                zero
+            }
+        }
+    }
     // sizeof(int)
     // 6.5.3.4 The sizeof and _Alignof operators
     fn sizeof(&mut self, ctype: Type, location: Location) -> Expr {
@@ -942,14 +1115,38 @@ pub(super) fn literal(literal: LiteralValue, location: Location) -> Expr {
             Type::Array(Box::new(Type::Char(true)), ArrayType::Fixed(len))
         }
     };
+    // 6.5.1p4: A string literal is a primary expression. It is an lvalue
+    // note that it still can't be modified, since it's an array
+    let lval = matches!(literal, LiteralValue::Str(_));
     Expr {
-        lval: false,
+        lval,
         ctype,
         location,
         expr: ExprType::Literal(literal),
     }
 }
 
+// 6.2.7 Compatible type and composite type
+// Two array types are compatible if their element types are,
+// and an array of unknown size is compatible with one of any size.
+fn compatible_types(left: &Type, right: &Type) -> bool {
+    use crate::data::types::ArrayType::Fixed;
+    match (left, right) {
+        (Type::Array(l, Fixed(n)), Type::Array(r, Fixed(m))) => n == m && compatible_types(l, r),
+        (Type::Array(l, _), Type::Array(r, _)) => compatible_types(l, r),
+        _ => left == right,
+    }
+}
+
+// Remove the top-level qualifiers of a pointer type.
+// Those of other types are never part of the `Type`.
+fn unqualified(ctype: Type) -> Type {
+    match ctype {
+        Type::Pointer(to, _) => Type::Pointer(to, Qualifiers::default()),
+        other => other,
+    }
+}
+
 // 6.5.15 - Conditional operator
 fn pointer_promote(left: &mut Expr, right: &mut Expr) -> bool {
     let is_convertible_to_any_pointer = |expr: &Expr| {
@@ -1403,6 +1600,12 @@ mod test {
             a.expr(expr)
         })
     }
+    fn gnu_expr(input: &str) -> CompileResult<Expr> {
+        analyze(input, Parser::expr, |a, expr| {
+            a.gnu_extensions(true);
+            a.expr(expr)
+        })
+    }
     fn assert_type(input: &str, ctype: Type) {
         match expr(input) {
             Ok(expr) => assert_eq!(expr.ctype, ctype),
@@ -1410,6 +1613,159 @@ mod test {
         };
     }
     #[test]
+    fn test_sizeof_folds() {
+        // x86_64 is LP64
+        for &(input, size) in &[
+            ("sizeof(char)", 1),
+            ("sizeof(int)", 4),
+            ("sizeof(long)", 8),
+            ("sizeof(int[4])", 16),
+            ("sizeof(int[2][3])", 24),
+            ("sizeof(void*)", 8),
+            // integer literals are `long`
+            ("sizeof 1", 8),
+        ] {
+            let parsed = expr(input).unwrap();
+            assert_eq!(
+                parsed.expr,
+                ExprType::Literal(LiteralValue::UnsignedInt(size))
+            );
+            assert_eq!(parsed.ctype, Type::Long(false));
+        }
+        // incomplete types have no size
+        assert!(expr("sizeof(void)").is_err());
+        assert!(expr("sizeof(int[])").is_err());
+    }
+    #[test]
+    fn test_imaginary_constant() {
+        assert_eq!(
+            expr("1.0i").unwrap_err().data,
+            SemanticError::ImaginaryConstant.into()
+        );
+        let gnu = analyze("1.0i", Parser::expr, |a, expr| {
+            a.gnu_extensions(true);
+            a.expr(expr)
+        });
+        assert_eq!(
+            gnu.unwrap_err().data,
+            SemanticError::ComplexNotSupported.into()
+        );
+    }
+    #[test]
+    fn test_implicit_function_declaration() {
+        let err = expr("f(1)").unwrap_err();
+        assert_eq!(
+            err.data,
+            SemanticError::ImplicitFunctionDeclaration("f".into()).into()
+        );
+        assert_eq!(
+            err.data.to_string(),
+            "invalid program: implicit declaration of function 'f' is not allowed since C99"
+        );
+        // C89 declares `extern int f();` instead
+        let (call, warnings) = analyze("f(1)", Parser::expr, |a, expr| {
+            a.c89(true);
+            (a.expr(expr), a.warnings())
+        })
+        .unwrap();
+        assert_eq!(call.ctype, Type::Int(true));
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(
+            warnings[0].data,
+            Warning::ImplicitFunctionDeclaration("f".into())
+        );
+        assert_eq!(
+            warnings[0].data.to_string(),
+            "implicit declaration of function 'f'"
+        );
+        // only calls get the special error
+        assert_eq!(
+            expr("f + 1").unwrap_err().data,
+            SemanticError::UndeclaredVar("f".into()).into()
+        );
+    }
+    #[test]
+    fn test_string_lval() {
+        let s = expr("\"abc\"").unwrap();
+        assert!(s.lval);
+        assert!(!expr("1").unwrap().lval);
+        // `&"abc"` is a pointer to an array, not a pointer to char
+        assert_type(
+            "&\"abc\"",
+            Type::Char(true)
+                .array_of(types::ArrayType::Fixed(4))
+                .pointer_to(Qualifiers::default()),
+        );
+        // but it's not modifiable
+        assert!(s.modifiable_lval().is_err());
+        assert!(expr("\"abc\" = 0").is_err());
+        assert!(expr("++\"abc\"").is_err());
+    }
+    #[test]
+    fn test_generic_selection() {
+        // integer literals are `long`
+        assert_type("_Generic(1, default: 'a', long: 1.0)", Type::Double);
+        assert_type("_Generic(1, long: 1.0, default: 'a')", Type::Double);
+        assert_type("_Generic(1.0, default: 'a', long: 1.0)", Type::Char(true));
+        // arrays decay to pointers
+        assert_type("_Generic(\"abc\", char *: 1.0, default: 'a')", Type::Double);
+        assert_eq!(
+            expr("_Generic(1.0, long: 1)").unwrap_err().data,
+            SemanticError::NoGenericAssociation(Type::Double).into()
+        );
+        assert_eq!(
+            expr("_Generic(1, default: 1, default: 2)")
+                .unwrap_err()
+                .data,
+            SemanticError::DuplicateGenericAssociation(None).into()
+        );
+        assert_eq!(
+            expr("_Generic(1, int: 1, int: 2)").unwrap_err().data,
+            SemanticError::DuplicateGenericAssociation(Some(Type::Int(true))).into()
+        );
+        // functions decay to (unqualified) function pointers
+        let void = Variable {
+            ctype: Type::Void,
+            id: Default::default(),
+            qualifiers: Default::default(),
+            storage_class: StorageClass::Auto,
+        };
+        let f = Variable {
+            id: InternedStr::get_or_intern("f"),
+            qualifiers: Default::default(),
+            storage_class: Default::default(),
+            ctype: Type::Int(true).function(vec![void.insert()], false),
+        }
+        .insert();
+        let selected = expr_with_scope("_Generic(f, int (*)(void): 1.0, default: 'a')", &[f]);
+        assert_eq!(selected.unwrap().ctype, Type::Double);
+        // compatible types are duplicates even if they aren't identical
+        let dup = expr("_Generic(1, int[]: 1, int[3]: 2)").unwrap_err().data;
+        assert!(matches!(
+            dup,
+            Error::Semantic(SemanticError::DuplicateGenericAssociation(_))
+        ));
+    }
+    #[test]
+    fn test_function_pointer_cast() {
+        assert_type(
+            "(int(*)[3])0",
+            Type::Int(true)
+                .array_of(types::ArrayType::Fixed(3))
+                .pointer_to(Qualifiers::default()),
+        );
+        assert_type("((void(*)(void))0)()", Type::Void);
+        assert_type("((int(*)(int))0)(1)", Type::Int(true));
+    }
+    #[test]
+    fn test_literal_suffix_types() {
+        assert_type("10", Type::Long(true));
+        assert_type("10u", Type::Long(false));
+        assert_type("10LL", Type::Long(true));
+        assert_type("0xFFu", Type::Long(false));
+        assert_type("0xFFul", Type::Long(false));
+    }
+    #[test]
     fn test_primaries() {
         assert_literal(LiteralValue::Int(141));
         let parsed = expr("\"hi there\"");
@@ -1486,6 +1842,91 @@ mod test {
         assert!(expr("0 ? \"error message\" : 0.0").is_err());
     }
 
+    #[test]
+    fn test_types_compatible() {
+        let compatible = |input| match gnu_expr(input).unwrap().expr {
+            ExprType::Literal(LiteralValue::Int(i)) => i,
+            other => panic!("expected a literal, got {:?}", other),
+        };
+        assert_eq!(
+            compatible("__builtin_types_compatible_p(int, unsigned int)"),
+            0
+        );
+        assert_eq!(compatible("__builtin_types_compatible_p(int, signed)"), 1);
+        assert_eq!(
+            compatible("__builtin_types_compatible_p(const int, int)"),
+            1
+        );
+        assert_eq!(compatible("__builtin_types_compatible_p(int *, long *)"), 0);
+        // top-level qualifiers of pointers are ignored, but not nested ones
+        assert_eq!(
+            compatible("__builtin_types_compatible_p(int *const, int *)"),
+            1
+        );
+        assert_eq!(
+            compatible("__builtin_types_compatible_p(int *const *, int **)"),
+            0
+        );
+        // arrays of unknown size are compatible with arrays of any size
+        assert_eq!(compatible("__builtin_types_compatible_p(int[], int[3])"), 1);
+        assert_eq!(compatible("__builtin_types_compatible_p(int[3], int[])"), 1);
+        assert_eq!(
+            compatible("__builtin_types_compatible_p(int[2], int[3])"),
+            0
+        );
+        assert_eq!(
+            compatible("__builtin_types_compatible_p(long[], int[3])"),
+            0
+        );
+        assert_eq!(
+            gnu_expr("__builtin_types_compatible_p(int, int)")
+                .unwrap()
+                .ctype,
+            Type::Int(true)
+        );
+        // this is a GNU extension
+        assert_eq!(
+            expr("__builtin_types_compatible_p(int, int)")
+                .unwrap_err()
+                .data,
+            SemanticError::GnuBuiltin("__builtin_types_compatible_p").into()
+        );
+    }
+
+    #[test]
+    fn test_choose_expr() {
+        let chosen = gnu_expr("__builtin_choose_expr(1, 2, 3.0)").unwrap();
+        assert_eq!(chosen.expr, ExprType::Literal(LiteralValue::Int(2)));
+        assert_eq!(chosen.ctype, Type::Long(true));
+        let chosen = gnu_expr("__builtin_choose_expr(1 - 1, 2, 3.0)").unwrap();
+        assert_eq!(chosen.expr, ExprType::Literal(LiteralValue::Float(3.0)));
+        assert_eq!(chosen.ctype, Type::Double);
+        // the unused branch is not evaluated
+        assert!(gnu_expr("__builtin_choose_expr(1, 2, undeclared)").is_ok());
+        // this is a GNU extension
+        assert_eq!(
+            expr("__builtin_choose_expr(1, 2, 3.0)").unwrap_err().data,
+            SemanticError::GnuBuiltin("__builtin_choose_expr").into()
+        );
+        let x = Variable {
+            ctype: Type::Int(true),
+            id: InternedStr::get_or_intern("x"),
+            qualifiers: Default::default(),
+            storage_class: Default::default(),
+        }
+        .insert();
+        let err = analyze("__builtin_choose_expr(x, 1, 2)", Parser::expr, |a, expr| {
+            a.gnu_extensions(true);
+            a.scope.insert(x.get().id, x);
+            a.expr(expr)
+        })
+        .unwrap_err();
+        match err.data {
+            Error::Semantic(SemanticError::NotConstant(_)) => {}
+            other => panic!("expected a constant expression error, got {}", other),
+        }
+    }
+
     #[test]
     fn test_explicit_casts() {
         assert_type("(int)4.2", Type::Int(true));
@@ -1,7 +1,8 @@
 //! 6.7.9 Initialization
 
 use super::PureAnalyzer;
-use crate::data::{ast, error::SemanticError, hir::*, types, Location, Type};
+use crate::data::{ast, error::SemanticError, hir::*, types, Locatable, Location, Type};
+use std::ops::RangeInclusive;
 
 impl PureAnalyzer {
     pub(super) fn parse_initializer(
@@ -10,12 +11,41 @@ impl PureAnalyzer {
         ctype: &Type,
         location: Location,
     ) -> Initializer {
-        use ast::Initializer::{Aggregate, Scalar};
+        use ast::Initializer::{Aggregate, Designated, Scalar};
         // initializer_list
-        let mut expr = match init {
+        let expr = match init {
             Aggregate(list) => return self.check_aggregate_overflow(list, ctype, location),
             Scalar(expr) => self.expr(*expr),
+            Designated(..) => unreachable!("parser should only allow designators in aggregates"),
         };
+        self.scalar_initializer(expr, ctype)
+    }
+
+    /// GNU `__auto_type x = 1;`: set `ctype` to the type of the initializer.
+    ///
+    /// The initializer is required and must be a single expression.
+    pub(super) fn auto_type_initializer(
+        &mut self,
+        init: Option<ast::Initializer>,
+        is_plain: bool,
+        ctype: &mut Type,
+        location: Location,
+    ) -> Option<Initializer> {
+        match init {
+            Some(ast::Initializer::Scalar(expr)) if is_plain => {
+                // arrays and functions decay to pointers, like in GCC
+                let expr = self.expr(*expr).rval();
+                *ctype = expr.ctype.clone();
+                Some(self.scalar_initializer(expr, ctype))
+            }
+            init => {
+                self.err(SemanticError::AutoTypeRequiresInitializer, location);
+                init.map(|init| self.parse_initializer(init, ctype, location))
+            }
+        }
+    }
+
+    fn scalar_initializer(&mut self, mut expr: Expr, ctype: &Type) -> Initializer {
         // The only time (that I know of) that an expression will initialize a non-scalar
         // is for character literals.
         let is_char_array = match ctype {
@@ -50,10 +80,15 @@ impl PureAnalyzer {
     ) -> Initializer {
         let len = list.len();
         let mut iter = list.into_iter().peekable();
-        let init = self.aggregate_initializer(&mut iter, ctype, location);
+        let init = self.aggregate_initializer(&mut iter, ctype, location, true);
         let leftover = iter.count();
         if leftover > 0 {
-            self.err(SemanticError::TooManyMembers(len - leftover, len), location);
+            let err = if let Type::Union(_) = ctype {
+                SemanticError::TooManyUnionInitializers(len)
+            } else {
+                SemanticError::TooManyMembers(len - leftover, len)
+            };
+            self.err(err, location);
         }
         init
     }
@@ -62,63 +97,91 @@ impl PureAnalyzer {
     // NOTE: this does NOT consume {} except for sub-elements
     // see p17: "Each brace-enclosed initializer list has an associated current object"
     // For each subobject of the enclosing object (`type_at`), initialize it, possibly recursively.
+    // `braced` is false if the braces for this object were elided.
     fn aggregate_initializer(
         &mut self,
         list: &mut std::iter::Peekable<impl Iterator<Item = ast::Initializer>>,
         elem_type: &Type,
         location: Location,
+        braced: bool,
     ) -> Initializer {
-        use ast::Initializer::{Aggregate, Scalar};
+        use ast::Initializer::{Aggregate, Designated, Scalar};
 
         let mut elems = vec![];
         if list.peek().is_none() {
             self.err(SemanticError::EmptyInitializer, location);
             return Initializer::InitializerList(elems);
         }
+        // the next subobject to initialize; a designator can move this anywhere in the object
+        let mut index = 0;
+        // the last subobject initialized; this is the member a union initializer initializes
+        let mut last = None;
         // char [][3] = {1};
         while let Some(elem) = list.peek() {
-            let inner = elem_type.type_at(elems.len()).unwrap_or_else(|err| {
-                // int a[1] = {1, 2};
-                self.err(err, location);
-                Type::Error
-            });
-            // int a[][3] = {{1,2,3}}
-            //               ^
-            // initializer is aggregate, type errors will be caught later
-
-            // If the initializer of a subaggregate or contained union begins with a left brace,
-            // the initializers enclosed by that brace and its matching right brace initialize
-            // the elements or members of the subaggregate or the contained union.
-            let next = match elem {
-                Aggregate(_) => match list.next() {
-                    Some(Aggregate(inner_list)) => {
-                        self.check_aggregate_overflow(inner_list, &inner, location)
-                    }
+            if let Designated(..) = elem {
+                // p17: a designator always refers to the current object of the enclosing braces,
+                // so any elided braces end here
+                if !braced {
+                    break;
+                }
+                let (designators, init) = match list.next() {
+                    Some(Designated(designators, init)) => (designators, *init),
                     _ => unreachable!(),
-                },
-                Scalar(_) => {
-                    // int a[][3] = {1,2,3}
-                    //               ^
-                    // type is aggregate and initializer is scalar
-                    // see if we can short circuit int[][3] -> int[3]
-                    if inner != Type::Error && !inner.is_scalar() {
-                        // Note: this element is _not_ consumed
-                        self.aggregate_initializer(list, &inner, location)
-                    // type is scalar and initializer is scalar
-                    // int a[][3] = {{1,2,3}}
-                    } else {
-                        let expr = match list.next() {
-                            Some(Scalar(expr)) => self
-                                .expr(*expr)
-                                .rval()
-                                .implicit_cast(&inner, &mut self.error_handler),
-                            _ => unreachable!(),
-                        };
-                        Initializer::Scalar(Box::new(expr))
-                    }
+                };
+                // TODO: initializers after `[0][1] = x` should continue with `[0][2]`, not `[1]`
+                if let Some(designated) =
+                    self.designated_initializer(designators, init, elem_type, &mut elems, location)
+                {
+                    index = designated + 1;
+                    last = Some(designated);
                 }
-            };
-            elems.push(next);
+            } else {
+                let inner = elem_type.type_at(index).unwrap_or_else(|err| {
+                    // int a[1] = {1, 2};
+                    self.err(err, location);
+                    Type::Error
+                });
+                // int a[][3] = {{1,2,3}}
+                //               ^
+                // initializer is aggregate, type errors will be caught later
+
+                // If the initializer of a subaggregate or contained union begins with a left brace,
+                // the initializers enclosed by that brace and its matching right brace initialize
+                // the elements or members of the subaggregate or the contained union.
+                let next = match elem {
+                    Aggregate(_) => match list.next() {
+                        Some(Aggregate(inner_list)) => {
+                            self.check_aggregate_overflow(inner_list, &inner, location)
+                        }
+                        _ => unreachable!(),
+                    },
+                    Scalar(_) => {
+                        // int a[][3] = {1,2,3}
+                        //               ^
+                        // type is aggregate and initializer is scalar
+                        // see if we can short circuit int[][3] -> int[3]
+                        if inner != Type::Error && !inner.is_scalar() {
+                            // Note: this element is _not_ consumed
+                            self.aggregate_initializer(list, &inner, location, false)
+                        // type is scalar and initializer is scalar
+                        // int a[][3] = {{1,2,3}}
+                        } else {
+                            let expr = match list.next() {
+                                Some(Scalar(expr)) => self
+                                    .expr(*expr)
+                                    .rval()
+                                    .implicit_cast(&inner, &mut self.error_handler),
+                                _ => unreachable!(),
+                            };
+                            Initializer::Scalar(Box::new(expr))
+                        }
+                    }
+                    Designated(..) => unreachable!(),
+                };
+                self.set_initializer(&mut elems, index, next, elem_type, location);
+                last = Some(index);
+                index += 1;
+            }
 
             // Otherwise, only enough initializers from the list are taken
             // to account for the elements or members of the subaggregate
@@ -126,15 +189,265 @@ impl PureAnalyzer {
             // any remaining initializers are left to initialize the next
             // element or member of the aggregate of which the current
             // subaggregate or contained union is a part.
-            if elems.len() == elem_type.type_len() {
-                break;
+            if index == elem_type.type_len() {
+                // int a[2] = {1, 2, [0] = 3};
+                let designator_next = matches!(list.peek(), Some(Designated(..)));
+                if !(braced && designator_next) {
+                    break;
+                }
+            }
+        }
+        Self::finish_aggregate(elems, elem_type, last)
+    }
+
+    /// Build the initializer for an aggregate from the initializers for its subobjects.
+    ///
+    /// Only one member of a union is initialized: the one initialized last.
+    fn finish_aggregate(
+        mut elems: Vec<Initializer>,
+        ctype: &Type,
+        last: Option<usize>,
+    ) -> Initializer {
+        match (ctype, last) {
+            (Type::Union(_), Some(member)) if member < elems.len() => {
+                Initializer::Union(member, Box::new(elems.swap_remove(member)))
+            }
+            _ => Initializer::InitializerList(elems),
+        }
+    }
+
+    /// 6.7.9p18 `.a[1] = x`: initialize the subobject of `ctype` named by `designators`.
+    ///
+    /// Returns the index in `ctype` of the last subobject named by the first designator,
+    /// or `None` if the designator was invalid (the error has already been reported).
+    fn designated_initializer(
+        &mut self,
+        designators: Vec<Locatable<ast::Designator>>,
+        init: ast::Initializer,
+        ctype: &Type,
+        elems: &mut Vec<Initializer>,
+        location: Location,
+    ) -> Option<usize> {
+        let mut designators = designators.into_iter();
+        let first = designators
+            .next()
+            .expect("parser should never return an empty designator list");
+        let rest: Vec<_> = designators.collect();
+        let indices = self.designator_indices(first, ctype)?;
+        let inner = match ctype {
+            // union { int a; float b; } u = {.b = 1};
+            Type::Union(stype) => stype.members()[*indices.start()].ctype.clone(),
+            _ => ctype.type_at(*indices.start()).unwrap_or_else(|err| {
+                self.err(err, location);
+                Type::Error
+            }),
+        };
+        if rest.is_empty() {
+            // `[0 ... 3] = x` gives every element the same value
+            let init = self.designated_value(init, &inner, location);
+            for index in indices.clone() {
+                self.set_initializer(elems, index, init.clone(), ctype, location);
+            }
+        } else {
+            for index in indices.clone() {
+                // `.a.x = 1, .a.y = 2` initializes both members of `a`
+                let empty = || Initializer::InitializerList(Vec::new());
+                let existing = elems.get_mut(index).map(|e| std::mem::replace(e, empty()));
+                let mut inner_elems = match existing {
+                    Some(Initializer::InitializerList(list)) => list,
+                    // `.u.a = 1, .u.b = 2` replaces the member of `u` that was initialized
+                    Some(Initializer::Union(member, init)) => {
+                        let mut list = Vec::new();
+                        self.set_initializer(&mut list, member, *init, &inner, location);
+                        list
+                    }
+                    _ => Vec::new(),
+                };
+                let (rest, init) = (rest.clone(), init.clone());
+                let last =
+                    self.designated_initializer(rest, init, &inner, &mut inner_elems, location);
+                let init = Self::finish_aggregate(inner_elems, &inner, last);
+                self.set_initializer(elems, index, init, ctype, location);
+            }
+        }
+        Some(*indices.end())
+    }
+
+    /// Analyze the initializer following a designator.
+    fn designated_value(
+        &mut self,
+        init: ast::Initializer,
+        ctype: &Type,
+        location: Location,
+    ) -> Initializer {
+        match init {
+            // `int a[2][2] = {[1] = 1}` initializes `a[1][0]`, as if the braces had been elided
+            ast::Initializer::Scalar(expr) if *ctype != Type::Error && !ctype.is_scalar() => {
+                let list = vec![ast::Initializer::Scalar(expr)];
+                self.check_aggregate_overflow(list, ctype, location)
+            }
+            init => self.parse_initializer(init, ctype, location),
+        }
+    }
+
+    /// Return the indices in `ctype` of the subobjects named by `designator`.
+    ///
+    /// This is only ever more than one index for GNU range designators.
+    fn designator_indices(
+        &mut self,
+        designator: Locatable<ast::Designator>,
+        ctype: &Type,
+    ) -> Option<RangeInclusive<usize>> {
+        use ast::Designator;
+
+        let location = designator.location;
+        match (designator.data, ctype) {
+            // int a[] = {[1] = 1};
+            (Designator::Index(expr), Type::Array(_, size)) => {
+                let index = self.designator_value(expr)?;
+                if !self.check_designator_bounds(index, size, location) {
+                    return None;
+                }
+                Some(index as usize..=index as usize)
+            }
+            // int a[] = {[1 ... 3] = 1};
+            (Designator::Range(low, high), Type::Array(_, size)) => {
+                // evaluate both bounds so errors in either are reported
+                let (low, high) = (self.designator_value(low), self.designator_value(high));
+                let (low, high) = (low?, high?);
+                if low > high {
+                    self.err(SemanticError::EmptyDesignatorRange(low, high), location);
+                    return None;
+                }
+                if !self.check_designator_bounds(high, size, location) {
+                    return None;
+                }
+                Some(low as usize..=high as usize)
+            }
+            // struct { int x; } s = {.x = 1};
+            (Designator::Field(name), Type::Struct(stype))
+            | (Designator::Field(name), Type::Union(stype)) => {
+                let members = stype.members();
+                match members.iter().position(|member| member.id == name) {
+                    Some(index) => Some(index..=index),
+                    None => {
+                        self.err(SemanticError::NotAMember(name, ctype.clone()), location);
+                        None
+                    }
+                }
+            }
+            // don't report cascading errors
+            (_, Type::Error) => None,
+            (Designator::Index(_), _) | (Designator::Range(..), _) => {
+                let err = SemanticError::ArrayDesignatorNonArray(ctype.clone());
+                self.err(err, location);
+                None
+            }
+            (Designator::Field(_), _) => {
+                self.err(SemanticError::NotAStruct(ctype.clone()), location);
+                None
+            }
+        }
+    }
+
+    /// Report an error if `index` is past the end of an array of `size`.
+    fn check_designator_bounds(
+        &mut self,
+        index: u64,
+        size: &types::ArrayType,
+        location: Location,
+    ) -> bool {
+        match *size {
+            types::ArrayType::Fixed(size) if index >= size => {
+                self.err(SemanticError::DesignatorOutOfBounds(index, size), location);
+                false
             }
+            _ => true,
+        }
+    }
+
+    /// Const-evaluate the index of an array designator.
+    fn designator_value(&mut self, expr: ast::Expr) -> Option<u64> {
+        let location = expr.location;
+        let expr = self.expr(expr);
+        // we've already reported this error
+        if expr.ctype == Type::Error {
+            return None;
+        }
+        match Self::const_sint(expr) {
+            Ok(index) if index < 0 => {
+                self.err(SemanticError::NegativeDesignator(index), location);
+                None
+            }
+            Ok(index) => Some(index as u64),
+            Err(err) => {
+                self.error_handler.push_back(err);
+                None
+            }
+        }
+    }
+
+    /// Store `init` as the initializer for subobject `index` of `ctype`,
+    /// initializing any subobjects before it that were skipped by a designator to zero.
+    fn set_initializer(
+        &mut self,
+        elems: &mut Vec<Initializer>,
+        index: usize,
+        init: Initializer,
+        ctype: &Type,
+        location: Location,
+    ) {
+        while elems.len() < index {
+            let skipped = ctype.type_at(elems.len()).unwrap_or(Type::Error);
+            let zero = self.zero_initializer(&skipped, location);
+            elems.push(zero);
+        }
+        if index < elems.len() {
+            elems[index] = init;
+        } else {
+            elems.push(init);
+        }
+    }
+
+    /// An initializer that sets every scalar in `ctype` to zero.
+    fn zero_initializer(&mut self, ctype: &Type, location: Location) -> Initializer {
+        match ctype {
+            ty if ty.is_scalar() => {
+                let zero = super::expr::literal(LiteralValue::Int(0), location);
+                let zero = zero.implicit_cast(ty, &mut self.error_handler);
+                Initializer::Scalar(Box::new(zero))
+            }
+            // the backend expects exactly one initializer for a union
+            Type::Union(stype) => {
+                let first = stype.members().first().map(|m| m.ctype.clone());
+                let first = first.unwrap_or(Type::Error);
+                Initializer::Union(0, Box::new(self.zero_initializer(&first, location)))
+            }
+            // members without an initializer are already zero
+            _ => Initializer::InitializerList(Vec::new()),
         }
-        Initializer::InitializerList(elems)
     }
 }
 
 impl Type {
+    /// Complete an array of unknown size using the length of its initializer.
+    ///
+    /// e.g. the type of `int a[] = {1, 2, 3};` is `int[3]`
+    pub(super) fn complete_from(&mut self, init: &Initializer) {
+        use types::ArrayType;
+        if let Type::Array(_, size @ ArrayType::Unbounded) = self {
+            let len = match init {
+                Initializer::InitializerList(list) => list.len(),
+                // char s[] = "hi";
+                Initializer::Scalar(expr) => match &expr.expr {
+                    ExprType::Literal(LiteralValue::Str(s)) => s.len(),
+                    _ => return,
+                },
+                Initializer::Union(..) | Initializer::FunctionBody(_) => return,
+            };
+            *size = ArrayType::Fixed(len as u64);
+        }
+    }
     /// Given a type, return the maximum number of initializers for that type
     fn type_len(&self) -> usize {
         use types::ArrayType;
@@ -142,7 +455,9 @@ impl Type {
             ty if ty.is_scalar() => 1,
             Type::Array(_, ArrayType::Fixed(size)) => *size as usize,
             Type::Array(_, ArrayType::Unbounded) => 0,
-            Type::Struct(st) | Type::Union(st) => st.members().len(),
+            Type::Struct(st) => st.members().len(),
+            // only the first member of a union is initialized
+            Type::Union(_) => 1,
             Type::Function { .. } | Type::Error => 1,
             _ => unimplemented!("type checking for {}", self),
         }
@@ -174,7 +489,7 @@ impl Type {
             }
             Type::Union(struct_type) => {
                 if index != 0 {
-                    return Err("can only initialize first member of an enum".into());
+                    return Err(SemanticError::TooManyUnionInitializers(index + 1));
                 }
                 let members = struct_type.members();
                 Ok(members
@@ -249,4 +564,186 @@ mod test {
         );
         assert_errs_decls("struct s { int *p; } s = { 1.0 }", 1, 0, 1);
     }
+    #[test]
+    fn test_union_initializers() {
+        let union = "union { int a; float b; } u";
+        assert_same(
+            &format!("{} = {{5}};", union),
+            &format!("{} = {{(int)5}};", union),
+        );
+        assert_eq!(
+            decl(&format!("{} = {{1, 2}};", union)).unwrap_err().data,
+            SemanticError::TooManyUnionInitializers(2).into()
+        );
+        assert_same(
+            &format!("{} = {{.a = 5}};", union),
+            &format!("{} = {{5}};", union),
+        );
+        match decl(&format!("{} = {{.b = 3}};", union)).unwrap().init {
+            Some(Initializer::Union(1, init)) => assert_eq!(init.to_string(), "(float)(3)"),
+            other => panic!("expected `b` to be initialized, got {:?}", other),
+        }
+        // the member named last is the one initialized
+        assert_same(
+            &format!("{} = {{.b = 3, .a = 5}};", union),
+            &format!("{} = {{5}};", union),
+        );
+        // a union nested in a struct only takes one initializer
+        assert_same(
+            "struct { union { int a; float b; } u; int i; } s = {1, 2};",
+            "struct { union { int a; float b; } u; int i; } s = {{1}, 2};",
+        );
+    }
+    #[test]
+    fn test_multidimensional_initializers() {
+        let nested = "int m[2][3] = {{1,2,3},{4,5,6}};";
+        assert_same("int m[2][3] = {1,2,3,4,5,6};", nested);
+        assert_same("int m[2][3] = {{1,2,3}, 4,5,6};", nested);
+        assert_same("int m[2][3] = {1,2,3, {4,5,6}};", nested);
+        assert_same(
+            "int m[][3] = {1,2,3, {4,5,6}};",
+            "int m[][3] = {{1,2,3},{4,5,6}};",
+        );
+        assert_same("int m[2][3] = {{1}, 4};", "int m[2][3] = {{1},{4}};");
+        assert!(decl("int m[2][3] = {1,2,3,4,5,6,7};").is_err());
+    }
+    #[test]
+    fn test_designated_initializers() {
+        assert_same("int a[3] = {[1] = 2};", "int a[3] = {0, 2};");
+        assert_same("int a[3] = {[2] = 3, [0] = 1};", "int a[3] = {1, 0, 3};");
+        // positional initializers continue after the designated element
+        assert_same("int a[4] = {[1] = 2, 3};", "int a[4] = {0, 2, 3};");
+        // later initializers override earlier ones
+        assert_same("int a[2] = {1, 2, [0] = 3};", "int a[2] = {3, 2};");
+        assert_same("int a[] = {[4] = 1};", "int a[5] = {0, 0, 0, 0, 1};");
+        // designators are constant expressions
+        let last = |s| decls(s).pop().unwrap();
+        assert_eq!(
+            last("enum { N = 2 }; int a[3] = {[N] = 1};")
+                .unwrap()
+                .to_string(),
+            decl("int a[3] = {0, 0, 1};").unwrap().to_string()
+        );
+        assert!(decls("int x; int a[2] = {[x] = 1};")
+            .iter()
+            .any(Result::is_err));
+
+        let point = "struct { int x; int y; } p";
+        assert_same(
+            &format!("{} = {{.y = 2}};", point),
+            &format!("{} = {{0, 2}};", point),
+        );
+        assert_same(
+            &format!("{} = {{.y = 2, .x = 1}};", point),
+            &format!("{} = {{1, 2}};", point),
+        );
+        // nested designators
+        assert_same(
+            "struct { int a[2]; } s = {.a[1] = 1};",
+            "struct { int a[2]; } s = {{0, 1}};",
+        );
+        assert_same(
+            "int m[2][2] = {[1][0] = 1, [1][1] = 2};",
+            "int m[2][2] = {[1] = {1, 2}};",
+        );
+
+        let err = |s| decl(s).unwrap_err().data;
+        assert_eq!(
+            err("int a[2] = {[2] = 1};"),
+            SemanticError::DesignatorOutOfBounds(2, 2).into()
+        );
+        assert_eq!(
+            err("int a[2] = {[-1] = 1};"),
+            SemanticError::NegativeDesignator(-1).into()
+        );
+        assert_eq!(
+            err("int i = {[0] = 1};"),
+            SemanticError::ArrayDesignatorNonArray(Type::Int(true)).into()
+        );
+        assert_eq!(
+            err("int a[1] = {.x = 1};"),
+            SemanticError::NotAStruct(Type::Array(
+                Box::new(Type::Int(true)),
+                types::ArrayType::Fixed(1)
+            ))
+            .into()
+        );
+        assert!(decl(&format!("{} = {{.z = 1}};", point)).is_err());
+    }
+    #[test]
+    fn test_gnu_field_designator() {
+        use crate::parse::test::parser;
+        use crate::Analyzer;
+
+        // `x:` is the same as `.x =`
+        let point = "struct { int x; int y; } p";
+        let gnu = format!("{} = {{ y: 2, x: 1 }};", point);
+        let mut analyzer = Analyzer::new(parser(&gnu).gnu_extensions(true), false);
+        let gnu = analyzer.next().unwrap().unwrap();
+        assert!(analyzer.next().is_none());
+        assert_eq!(
+            gnu.data.to_string(),
+            decl(&format!("{} = {{1, 2}};", point)).unwrap().to_string()
+        );
+    }
+    #[test]
+    fn test_designator_range() {
+        use crate::parse::test::parser;
+        use crate::Analyzer;
+
+        // errors are returned before the declaration they belong to
+        let gnu = |s| {
+            let analyzer = Analyzer::new(parser(s).gnu_extensions(true), false);
+            let mut results: Vec<_> = analyzer.collect();
+            match results.iter().position(Result::is_err) {
+                Some(i) => results.remove(i),
+                None => results.pop().unwrap(),
+            }
+        };
+        let range = gnu("int a[5] = {[1 ... 3] = 7};").unwrap();
+        let elems = match &range.data.init {
+            Some(Initializer::InitializerList(elems)) => elems,
+            init => panic!("expected an initializer list, got {:?}", init),
+        };
+        let values: Vec<_> = elems
+            .iter()
+            .map(|elem| match elem {
+                Initializer::Scalar(expr) => expr.to_string(),
+                _ => panic!("expected a scalar, got {:?}", elem),
+            })
+            .collect();
+        assert_eq!(values, vec!["(int)(0)", "(int)(7)", "(int)(7)", "(int)(7)"]);
+        assert_eq!(
+            range.data.to_string(),
+            decl("int a[5] = {0, 7, 7, 7};").unwrap().to_string()
+        );
+        // positional initializers continue after the end of the range
+        assert_eq!(
+            gnu("int a[5] = {[1 ... 2] = 7, 8};")
+                .unwrap()
+                .data
+                .to_string(),
+            decl("int a[5] = {0, 7, 7, 8};").unwrap().to_string()
+        );
+        assert_eq!(
+            gnu("int a[] = {[0 ... 2] = 1};").unwrap().data.to_string(),
+            decl("int a[3] = {1, 1, 1};").unwrap().to_string()
+        );
+
+        // both bounds are constant expressions
+        assert_eq!(
+            gnu("enum { N = 3 }; int a[5] = {[N ... 1] = 7};")
+                .unwrap_err()
+                .data,
+            SemanticError::EmptyDesignatorRange(3, 1).into()
+        );
+        assert_eq!(
+            gnu("int a[5] = {[3 ... 5] = 7};").unwrap_err().data,
+            SemanticError::DesignatorOutOfBounds(5, 5).into()
+        );
+        assert_eq!(
+            gnu("int i = {[0 ... 1] = 7};").unwrap_err().data,
+            SemanticError::ArrayDesignatorNonArray(Type::Int(true)).into()
+        );
+    }
 }
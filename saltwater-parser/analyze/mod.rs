@@ -13,6 +13,7 @@ use crate::parse::{Lexer, Parser};
 use crate::RecursionGuard;
 
 pub(crate) type TagScope = Scope<InternedStr, TagEntry>;
+type DeclarationHook = Box<dyn FnMut(&Symbol, &Location)>;
 
 #[derive(Clone, Debug)]
 pub(crate) enum TagEntry {
@@ -72,6 +73,14 @@ pub struct PureAnalyzer {
     ///
     /// TODO: this should be a field on `FunctionAnalyzer`, not `Analyzer`
     decl_side_channel: Vec<Locatable<Declaration>>,
+    /// Called for each symbol as soon as it is declared, see `on_declaration`
+    declaration_hook: Option<DeclarationHook>,
+    /// Whether to accept GNU extensions that ISO C forbids, see `gnu_extensions`
+    gnu_extensions: bool,
+    /// Whether a plain `char` is signed, see `char_is_signed`
+    char_is_signed: bool,
+    /// Whether to follow C89 where it differs from later standards, see `c89`
+    c89: bool,
 }
 
 impl<T: Lexer> Iterator for Analyzer<T> {
@@ -92,13 +101,76 @@ impl<T: Lexer> Iterator for Analyzer<T> {
                 return Some(Ok(decl));
             }
             // Now do the real work.
-            let next = match self.declarations.next()? {
+            if let Err(err) = self.analyze_next()? {
+                return Some(Err(err));
+            }
+        }
+    }
+}
+
+/// An iterator adapter for `Analyzer` that keeps declarators from the same declaration together.
+///
+/// `Analyzer` splits a declaration with several declarators,
+/// such as `int a, b, c;`, into one `Declaration` per symbol.
+/// `Grouped` instead returns all the declarations from a single declaration at once,
+/// in the order they were declared.
+///
+/// Construct this with `Analyzer::grouped`.
+pub struct Grouped<T: Lexer>(Analyzer<T>);
+
+impl<T: Lexer> Iterator for Grouped<T> {
+    type Item = CompileResult<Vec<Locatable<Declaration>>>;
+    fn next(&mut self) -> Option<Self::Item> {
+        let analyzer = &mut self.0;
+        loop {
+            // NOTE: as with `Analyzer`, errors are returned before the declarations they belong to
+            if let Some(err) = analyzer.inner.error_handler.pop_front() {
+                return Some(Err(err));
+            } else if !analyzer.inner.pending.is_empty() {
+                let group: Vec<_> = analyzer.inner.pending.drain(..).collect();
+                if analyzer.debug {
+                    for decl in &group {
+                        println!("hir: {}", decl.data);
+                    }
+                }
+                return Some(Ok(group));
+            }
+            if let Err(err) = analyzer.analyze_next()? {
+                return Some(Err(err));
+            }
+        }
+    }
+}
+
+/// An iterator adapter for `Analyzer` that numbers the declaration each symbol came from.
+///
+/// Like `Analyzer`, this returns one `Declaration` per symbol,
+/// but pairs it with a monotonically increasing declaration index,
+/// so `int a, b, c; int d;` gives `a`, `b`, and `c` the same index and `d` a larger one.
+///
+/// Construct this with `Analyzer::indexed`.
+pub struct Indexed<T: Lexer> {
+    groups: Grouped<T>,
+    current: VecDeque<Locatable<Declaration>>,
+    index: usize,
+}
+
+impl<T: Lexer> Iterator for Indexed<T> {
+    type Item = CompileResult<(usize, Locatable<Declaration>)>;
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(decl) = self.current.pop_front() {
+                return Some(Ok((self.index, decl)));
+            }
+            match self.groups.next()? {
+                Ok(group) => {
+                    if !group.is_empty() {
+                        self.index += 1;
+                    }
+                    self.current = group.into();
+                }
                 Err(err) => return Some(Err(err)),
-                Ok(decl) => decl,
-            };
-            let decls = self.inner.parse_external_declaration(next);
-            // TODO: if an error occurs, should we still add the declaration to `pending`?
-            self.inner.pending.extend(decls);
+            }
         }
     }
 }
@@ -111,6 +183,39 @@ impl<I: Lexer> Analyzer<I> {
             inner: PureAnalyzer::new(),
         }
     }
+    /// Return declarations grouped by the declaration they came from, see `Grouped`.
+    pub fn grouped(self) -> Grouped<I> {
+        Grouped(self)
+    }
+    /// Return declarations paired with the index of the declaration they came from, see `Indexed`.
+    pub fn indexed(self) -> Indexed<I> {
+        Indexed {
+            groups: self.grouped(),
+            current: VecDeque::new(),
+            index: 0,
+        }
+    }
+    /// Return all warnings seen so far, both while parsing and while analyzing.
+    ///
+    /// These warnings are consumed and will not be returned if you call
+    /// `warnings()` again.
+    pub fn warnings(&mut self) -> VecDeque<CompileWarning> {
+        let mut warnings = self.declarations.warnings();
+        warnings.extend(self.inner.warnings());
+        warnings
+    }
+    // Analyze the next external declaration and store the results in `pending`.
+    // Returns `None` at the end of the input.
+    fn analyze_next(&mut self) -> Option<CompileResult<()>> {
+        let next = match self.declarations.next()? {
+            Err(err) => return Some(Err(err)),
+            Ok(decl) => decl,
+        };
+        let decls = self.inner.parse_external_declaration(next);
+        // TODO: if an error occurs, should we still add the declaration to `pending`?
+        self.inner.pending.extend(decls);
+        Some(Ok(()))
+    }
 }
 
 impl Default for PureAnalyzer {
@@ -129,6 +234,39 @@ impl PureAnalyzer {
             initialized: HashSet::new(),
             recursion_guard: RecursionGuard::default(),
             decl_side_channel: Vec::new(),
+            declaration_hook: None,
+            gnu_extensions: false,
+            char_is_signed: true,
+            c89: false,
+        }
+    }
+    /// Call `f` for each symbol declared from now on, including block-scope variables
+    /// and function definitions, but not function parameters.
+    ///
+    /// This is useful for tools that want to index symbols without matching on every declaration.
+    /// Only one callback can be registered at a time; registering another replaces the first.
+    pub fn on_declaration(&mut self, f: impl FnMut(&Symbol, &Location) + 'static) {
+        self.declaration_hook = Some(Box::new(f));
+    }
+    /// Accept GNU extensions that are errors in ISO C, such as `int f(...)`.
+    pub fn gnu_extensions(&mut self, yes: bool) {
+        self.gnu_extensions = yes;
+    }
+    /// Choose whether `char` without `signed` or `unsigned` is signed, like `-fsigned-char`.
+    ///
+    /// This is implementation-defined, so it defaults to `true`.
+    pub fn char_is_signed(&mut self, yes: bool) {
+        self.char_is_signed = yes;
+    }
+    /// Follow C89 where it differs from later standards.
+    ///
+    /// For example, calling an undeclared function is a warning instead of an error.
+    pub fn c89(&mut self, yes: bool) {
+        self.c89 = yes;
+    }
+    fn run_declaration_hook(&mut self, symbol: Symbol, location: Location) {
+        if let Some(hook) = &mut self.declaration_hook {
+            hook(&symbol, &location);
         }
     }
 
@@ -166,6 +304,7 @@ impl PureAnalyzer {
                 let id = func.id;
                 let (meta_ref, body) = FunctionAnalyzer::analyze(func, self, next.location);
                 self.scope.insert(id, meta_ref);
+                self.run_declaration_hook(meta_ref, next.location);
                 let decl = Declaration {
                     symbol: meta_ref,
                     init: Some(Initializer::FunctionBody(body)),
@@ -188,11 +327,20 @@ impl PureAnalyzer {
         if original.storage_class == Some(StorageClass::Auto) && self.scope.is_global() {
             self.err(SemanticError::AutoAtGlobalScope, location);
         }
+        // typedef int F(void); const F f;
+        // NOTE: `const int f(void)` is fine, the `const` goes on the return type
+        if original.ctype.is_function()
+            && (original.qualifiers.c_const || original.qualifiers.volatile)
+        {
+            self.err(SemanticError::QualifiedFunctionType, location);
+        }
 
         // TODO: this is such a hack: https://github.com/jyn514/rcc/issues/371
         let sc = original.storage_class.unwrap_or(StorageClass::Auto);
         let mut decls = Vec::new();
         for d in declaration.declarators {
+            // `__auto_type *p = &x;` isn't allowed
+            let is_plain = d.data.declarator.decl == ast::DeclaratorType::End;
             let mut ctype =
                 self.parse_declarator(original.ctype.clone(), d.data.declarator.decl, d.location);
 
@@ -212,17 +360,39 @@ impl PureAnalyzer {
                     "<error>".into()
                 }
             };
+            // int f(void) = 0;
+            let init = match d.data.init {
+                Some(_) if ctype.is_function() => {
+                    self.err(SemanticError::FunctionInitializer(id), d.location);
+                    None
+                }
+                init => init,
+            };
             // NOTE: the parser handles typedefs on its own
             if ctype == Type::Void && sc != StorageClass::Typedef {
                 // TODO: catch this error for types besides void?
                 self.err(SemanticError::VoidType, location);
                 ctype = Type::Error;
             }
-            let init = if let Some(init) = d.data.init {
-                Some(self.parse_initializer(init, &ctype, d.location))
+            let decl_location = d.location;
+            let init = if original.auto_type {
+                self.auto_type_initializer(init, is_plain, &mut ctype, decl_location)
             } else {
-                None
+                init.map(|init| self.parse_initializer(init, &ctype, decl_location))
             };
+            if let Some(init) = &init {
+                // char *argv[] = {"a", "b"};
+                ctype.complete_from(init);
+            } else if let Type::Array(_, types::ArrayType::Unbounded) = ctype {
+                match sc {
+                    StorageClass::Extern | StorageClass::Typedef => {}
+                    // int a[]; int a[3];
+                    _ if self.scope.is_global() => {
+                        self.warn(Warning::IncompleteArray(id), d.location)
+                    }
+                    _ => self.err(SemanticError::ArraySizeMissing(id), d.location),
+                }
+            }
             let symbol = Variable {
                 ctype,
                 id,
@@ -233,6 +403,7 @@ impl PureAnalyzer {
             if init.is_some() {
                 self.initialized.insert(symbol);
             }
+            self.run_declaration_hook(symbol, d.location);
             decls.push(Locatable::new(Declaration { symbol, init }, d.location));
         }
         // int;
@@ -278,6 +449,10 @@ impl PureAnalyzer {
         location: Location,
     ) -> ParsedType {
         let mut specs = self.parse_specifiers(specifiers, location);
+        // `(__auto_type)1` or `int f(__auto_type x)`
+        if specs.auto_type {
+            self.err(SemanticError::AutoTypeRequiresInitializer, location);
+        }
         specs.ctype = self.parse_declarator(specs.ctype, declarator, location);
 
         if !specs.ctype.is_function() && specs.qualifiers.func != FunctionQualifiers::default() {
@@ -349,7 +524,7 @@ impl PureAnalyzer {
             }
         }
         // 6.7.3 Type qualifiers
-        let qualifiers = Qualifiers {
+        let mut qualifiers = Qualifiers {
             c_const: counter.get(&Const).is_some(),
             volatile: counter.get(&Volatile).is_some(),
             func: FunctionQualifiers {
@@ -377,11 +552,17 @@ impl PureAnalyzer {
             }
         }
         // back to type specifiers
+        // 6.2.5p15: whether plain `char` is signed is implementation-defined
+        let char_signed = if counter.get(&Signed).is_none() && counter.get(&Unsigned).is_none() {
+            self.char_is_signed
+        } else {
+            signed
+        };
         // TODO: maybe use `iter!` macro instead of `vec!` to avoid an allocation?
         // https://play.rust-lang.org/?gist=0535aa4f749a14cb1b28d658446f3c13
         for (spec, new_ctype) in vec![
             (Bool, Type::Bool),
-            (Char, Type::Char(signed)),
+            (Char, Type::Char(char_signed)),
             (Short, Type::Short(signed)),
             // already handled `long` when we handled `long long`
             (Float, Type::Float),
@@ -397,6 +578,12 @@ impl PureAnalyzer {
                     // `long` is handled earlier, so we don't have to worry
                     // about it here.
                     (_, None) | (Short, Some(Type::Int(_))) => {}
+                    // `long char` or `short long`
+                    (Char, Some(Type::Long(_))) | (Short, Some(Type::Long(_))) => {
+                        self.err(SemanticError::LongWith(spec), location);
+                        ctype = Some(Type::Long(signed));
+                        continue;
+                    }
                     (_, Some(existing)) => {
                         self.err(
                             SemanticError::ConflictingType(existing, new_ctype.clone()),
@@ -432,6 +619,19 @@ impl PureAnalyzer {
                         .expect("scope of parser and analyzer should match")
                         .get();
                     assert_eq!(meta.storage_class, StorageClass::Typedef);
+                    // `typedef const int T; T x;` makes `x` const, even if `T` is an array.
+                    // For pointers and functions, the qualifiers we store belong to the type
+                    // pointed to or returned (`typedef const char *S`, `typedef const int F(void)`,
+                    // `typedef const char *A[2]`), so they must not be copied to the variable.
+                    let mut element = &meta.ctype;
+                    while let Type::Array(inner, _) = element {
+                        element = inner;
+                    }
+                    let derived = matches!(element, Type::Pointer(..) | Type::Function(_));
+                    if !derived {
+                        qualifiers.c_const |= meta.qualifiers.c_const;
+                        qualifiers.volatile |= meta.qualifiers.volatile;
+                    }
                     meta.ctype.clone()
                 }
                 Struct(s) => self.struct_specifier(s, true, &mut declared_compound_type, location),
@@ -466,16 +666,35 @@ impl PureAnalyzer {
                 None => ctype = Some(Type::Int(signed)),
             }
         }
+        // GNU `__auto_type x = 1;`: the real type is filled in by `parse_declaration`
+        let auto_type = counter.get(&AutoType).is_some();
+        if auto_type {
+            if !self.gnu_extensions {
+                self.err(SemanticError::AutoTypeExtension, location);
+            }
+            if let Some(existing) = ctype.replace(Type::Error) {
+                self.err(SemanticError::AutoTypeWithType(existing), location);
+            }
+        }
         // `i;` or `const i;`, etc.
         let ctype = ctype.unwrap_or_else(|| {
             self.warn(Warning::ImplicitInt, location);
             Type::Int(true)
         });
+        // 6.7.3p2: `restrict int i;` or `int restrict *p;`
+        // `*restrict` is handled by `pointer_qualifiers`
+        if counter.get(&Restrict).is_some() && !ctype.is_pointer() && ctype != Type::Error {
+            self.err(
+                SemanticError::RestrictWithoutPointer(ctype.clone()),
+                location,
+            );
+        }
         ParsedType {
             qualifiers,
             storage_class,
             ctype,
             declared_compound_type,
+            auto_type,
         }
     }
     // 6.7.2.1 Structure and union specifiers
@@ -492,6 +711,8 @@ impl PureAnalyzer {
             // struct s
             None => {
                 let name = if let Some(name) = struct_spec.name {
+                    // `struct s;` declares `s`, even if it's never defined
+                    *declared_struct = true;
                     name
                 } else {
                     // struct;
@@ -590,6 +811,9 @@ impl PureAnalyzer {
         location: Location,
     ) -> Vec<Variable> {
         let parsed_type = self.parse_specifiers(members.specifiers, location);
+        if parsed_type.auto_type {
+            self.err(SemanticError::AutoTypeRequiresInitializer, location);
+        }
         if parsed_type.qualifiers.has_func_qualifiers() {
             self.err(
                 SemanticError::FuncQualifiersNotAllowed(parsed_type.qualifiers.func),
@@ -828,6 +1052,37 @@ impl PureAnalyzer {
         self.tag_scope.insert(ident, entry);
         tag_type(StructType::Named(ident, struct_ref))
     }
+    /// The qualifiers for a pointer: `*const volatile`
+    fn pointer_qualifiers(
+        &mut self,
+        qualifiers: Vec<ast::DeclarationSpecifier>,
+        location: Location,
+    ) -> Qualifiers {
+        use UnitSpecifier::*;
+
+        // we reuse `count_specifiers` even though we really only want the qualifiers
+        let (counter, compounds) = count_specifiers(qualifiers, &mut self.error_handler, location);
+        // TODO: this shouldn't allow `inline` or `_Noreturn`
+        let qualifiers = Qualifiers {
+            c_const: counter.get(&Const).is_some(),
+            volatile: counter.get(&Volatile).is_some(),
+            func: FunctionQualifiers {
+                inline: counter.get(&Inline).is_some(),
+                no_return: counter.get(&NoReturn).is_some(),
+            },
+        };
+        for &q in counter.keys() {
+            if !q.is_qualifier() {
+                // *extern
+                self.err(SemanticError::NotAQualifier(q.into()), location);
+            }
+        }
+        for spec in compounds {
+            // *struct s {}
+            self.err(SemanticError::NotAQualifier(spec), location);
+        }
+        qualifiers
+    }
     /// Parse the declarator for a variable, given a starting type.
     /// e.g. for `int *p`, takes `start: Type::Int(true)` and returns `Type::Pointer(Type::Int(true))`
     ///
@@ -848,41 +1103,23 @@ impl PureAnalyzer {
         match decl {
             End => current,
             Pointer { to, qualifiers } => {
-                use UnitSpecifier::*;
-
                 let inner = self.parse_declarator(current, *to, location);
-                // we reuse `count_specifiers` even though we really only want the qualifiers
-                let (counter, compounds) =
-                    count_specifiers(qualifiers, &mut self.error_handler, location);
-                // *const volatile
-                // TODO: this shouldn't allow `inline` or `_Noreturn`
-                let qualifiers = Qualifiers {
-                    c_const: counter.get(&Const).is_some(),
-                    volatile: counter.get(&Volatile).is_some(),
-                    func: FunctionQualifiers {
-                        inline: counter.get(&Inline).is_some(),
-                        no_return: counter.get(&NoReturn).is_some(),
-                    },
-                };
-                for &q in counter.keys() {
-                    if !q.is_qualifier() {
-                        // *extern
-                        self.err(SemanticError::NotAQualifier(q.into()), location);
-                    }
-                }
-                for spec in compounds {
-                    // *struct s {}
-                    self.err(SemanticError::NotAQualifier(spec), location);
-                }
+                let qualifiers = self.pointer_qualifiers(qualifiers, location);
                 Type::Pointer(Box::new(inner), qualifiers)
             }
-            Array { of, size } => {
+            Array { of, size, .. } => {
                 // int a[5]
                 let size = if let Some(expr) = size {
-                    let size = Self::const_uint(self.expr(*expr)).unwrap_or_else(|err| {
-                        self.error_handler.push_back(err);
+                    let expr = self.expr(*expr);
+                    // we've already reported this error
+                    let size = if expr.ctype == Type::Error {
                         1
-                    });
+                    } else {
+                        Self::const_uint(expr).unwrap_or_else(|err| {
+                            self.error_handler.push_back(err);
+                            1
+                        })
+                    };
                     ArrayType::Fixed(size)
                 } else {
                     // int a[]
@@ -915,13 +1152,19 @@ impl PureAnalyzer {
                 let mut names = HashSet::new();
                 let mut params = Vec::new();
                 for param in func.params {
+                    // `int f(int a[const])` -> `int f(int *const a)`
+                    let array_qualifiers = match &param.declarator.decl {
+                        ast::DeclaratorType::Array { qualifiers, .. } => qualifiers.clone(),
+                        _ => Vec::new(),
+                    };
                     // TODO: this location should be that of the param, not of the function
                     let mut param_type =
                         self.parse_type(param.specifiers, param.declarator.decl, location);
 
                     // `int f(int a[])` -> `int f(int *a)`
                     if let Type::Array(to, _) = param_type.ctype {
-                        param_type.ctype = Type::Pointer(to, Qualifiers::default());
+                        let qualifiers = self.pointer_qualifiers(array_qualifiers, location);
+                        param_type.ctype = Type::Pointer(to, qualifiers);
                     }
 
                     // C11 Standard 6.7.6.3 paragraph 8
@@ -934,14 +1177,25 @@ impl PureAnalyzer {
                     }
 
                     // int a(extern int i)
-                    if let Some(sc) = param_type.storage_class {
-                        self.err(SemanticError::ParameterStorageClass(sc), location);
-                    }
+                    // 6.7.6.3p2: "The only storage-class specifier that shall occur in a parameter declaration is register."
+                    let storage_class = match param_type.storage_class {
+                        Some(StorageClass::Register) => StorageClass::Register,
+                        Some(sc) => {
+                            self.err(SemanticError::ParameterStorageClass(sc), location);
+                            StorageClass::Auto
+                        }
+                        None => StorageClass::Auto,
+                    };
                     let id = if let Some(name) = param.declarator.id {
                         // int f(int a, int a)
                         if names.contains(&name) {
                             self.err(SemanticError::DuplicateParameter(name), location)
                         }
+                        // int f(void a)
+                        if param_type.ctype == Type::Void {
+                            self.err(SemanticError::NamedVoidParameter(name), location);
+                            param_type.ctype = Type::Error;
+                        }
                         names.insert(name);
                         name
                     } else {
@@ -952,7 +1206,7 @@ impl PureAnalyzer {
                         ctype: param_type.ctype,
                         id,
                         qualifiers: param_type.qualifiers,
-                        storage_class: StorageClass::Auto,
+                        storage_class,
                     };
                     params.push(meta);
                 }
@@ -975,7 +1229,7 @@ impl PureAnalyzer {
                 } else if func.varargs && is_void {
                     self.err(SemanticError::VoidVarargs, location);
                 // int f(...)
-                } else if func.varargs && params.is_empty() {
+                } else if func.varargs && params.is_empty() && !self.gnu_extensions {
                     self.err(SemanticError::VarargsWithoutParam, location);
                 }
                 Type::Function(FunctionType {
@@ -1139,7 +1393,7 @@ impl Type {
     #[inline]
     fn is_char(&self) -> bool {
         match self {
-            Type::Char(true) => true,
+            Type::Char(_) => true,
             _ => false,
         }
     }
@@ -1154,6 +1408,15 @@ struct FunctionAnalyzer<'a> {
     metadata: FunctionData,
     /// We need this for the scopes, as well as for parsing expressions
     analyzer: &'a mut PureAnalyzer,
+    /// The loops and switches around the current statement, innermost last.
+    /// Used to check that `break` and `continue` have somewhere to go.
+    contexts: Vec<BreakContext>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum BreakContext {
+    Loop,
+    Switch,
 }
 
 #[derive(Debug)]
@@ -1215,6 +1478,7 @@ impl FunctionAnalyzer<'_> {
         let mut func_analyzer = FunctionAnalyzer {
             metadata: tmp_metadata,
             analyzer,
+            contexts: Vec::new(),
         };
         func_analyzer.enter_scope();
         for (i, param) in func_type.params.into_iter().enumerate() {
@@ -1284,6 +1548,8 @@ struct ParsedType {
     ctype: Type,
     // TODO: this is fishy
     declared_compound_type: bool,
+    // `__auto_type`: `ctype` is a placeholder until the initializer is known
+    auto_type: bool,
 }
 
 use ast::{DeclarationSpecifier, UnitSpecifier};
@@ -1304,19 +1570,20 @@ fn count_specifiers(
             _ => compounds.push(spec),
         }
     }
+    let mut redundant_qualifiers = 0;
     for (&spec, &count) in counter.iter() {
-        if spec != Long && count > 1 {
-            if spec.is_type() {
-                let err = SemanticError::InvalidSpecifier {
-                    existing: spec.into(),
-                    new: spec.into(),
-                };
-                error_handler.error(err, location);
-            } else {
-                error_handler.warn(Warning::DuplicateSpecifier(spec, count), location);
-            }
+        if matches!(spec, Const | Volatile | Restrict) && count > 1 {
+            redundant_qualifiers += count - 1;
+        }
+        // duplicate type specifiers are errors, but the parser has already reported them
+        if spec != Long && count > 1 && !spec.is_type() {
+            error_handler.warn(Warning::DuplicateSpecifier(spec, count), location);
         }
     }
+    // `const volatile const const int` is legal, but it's probably not what the user meant
+    if redundant_qualifiers > 1 {
+        error_handler.warn(Warning::RedundantQualifiers(redundant_qualifiers), location);
+    }
     (counter, compounds)
 }
 
@@ -1331,10 +1598,10 @@ impl UnitSpecifier {
     /// Returns whether this is a self-contained type, not just whether this modifies a type.
     /// For example, `int` and `long` are self-contained types, but `unsigned` and `_Complex` are not.
     /// This is despite the fact that `unsigned i;` is valid and means `unsigned int i;`
-    fn is_type(self) -> bool {
+    pub(crate) fn is_type(self) -> bool {
         use UnitSpecifier::*;
         match self {
-            Bool | Char | Int | Long | Float | Double | VaList => true,
+            Bool | Char | Int | Long | Float | Double | VaList | AutoType => true,
             _ => false,
         }
     }
@@ -1471,6 +1738,8 @@ pub(crate) mod test {
 
     #[test]
     fn storage_class() {
+        use crate::PreProcessorBuilder;
+
         assert_extern_decl_display("int i;");
         assert_eq!(
             decl("extern int i;").unwrap().symbol.get().storage_class,
@@ -1484,6 +1753,19 @@ pub(crate) mod test {
             Error::Semantic(SemanticError::AutoAtGlobalScope) => {}
             _ => panic!("wrong error"),
         }
+        // `__thread` doesn't change the storage class
+        let gnu = |s| {
+            let cpp = PreProcessorBuilder::new(s).gnu_extensions(true).build();
+            let decl = Analyzer::new(Parser::new(cpp, false), false)
+                .next()
+                .unwrap();
+            decl.unwrap().data.symbol.get().storage_class
+        };
+        assert_eq!(gnu("static __thread int i;"), StorageClass::Static);
+        assert_eq!(
+            gnu("__thread int i;"),
+            decl("int i;").unwrap().symbol.get().storage_class
+        );
     }
 
     #[test]
@@ -1553,6 +1835,78 @@ pub(crate) mod test {
         assert!(match_type(decl("const volatile i;"), Type::Int(true)));
     }
     #[test]
+    fn redundant_qualifiers() {
+        let warnings = |s| {
+            let mut a = Analyzer::new(parser(s), false);
+            assert!(a.all(|res| res.is_ok()));
+            a.inner
+                .warnings()
+                .into_iter()
+                .map(|w| w.data)
+                .collect::<Vec<_>>()
+        };
+        let summary = Warning::RedundantQualifiers(2);
+        assert!(warnings("const volatile const const int x;").contains(&summary));
+        assert!(warnings("const const volatile volatile int x;").contains(&summary));
+        // a single duplicate only gets the usual warning
+        assert_eq!(
+            warnings("const const int x;"),
+            vec![Warning::DuplicateSpecifier(UnitSpecifier::Const, 2)]
+        );
+    }
+    #[test]
+    fn restrict_requires_pointer() {
+        assert!(decl("int *restrict p;").is_ok());
+        assert!(decl("int f(int a[restrict]);").is_ok());
+        // `restrict` applies to the typedef'd pointer
+        assert!(decls("typedef int *P; restrict P p;")
+            .into_iter()
+            .all(|d| d.is_ok()));
+        for s in &["restrict int x;", "int restrict *p;"] {
+            let err = decl(s).unwrap_err().data;
+            assert_eq!(err, SemanticError::RestrictWithoutPointer(Int(true)).into());
+            assert_eq!(
+                err.to_string(),
+                "invalid program: restrict requires a pointer type, got 'int'"
+            );
+        }
+    }
+    #[test]
+    fn function_initializer() {
+        assert_eq!(
+            decl("int f(void) = 0;").unwrap_err().data,
+            SemanticError::FunctionInitializer("f".into()).into()
+        );
+        assert!(decl("int (f)(void) = 0;").is_err());
+        // the function is still declared
+        let mut results = decls("int f(void) = 0;");
+        assert_eq!(results.len(), 2);
+        let f = results.pop().unwrap().unwrap();
+        assert!(f.init.is_none());
+        assert!(f.symbol.get().ctype.is_function());
+    }
+    #[test]
+    fn test_long_with_char_or_short() {
+        for (s, spec) in &[
+            ("long char i;", UnitSpecifier::Char),
+            ("char long i;", UnitSpecifier::Char),
+            ("short long i;", UnitSpecifier::Short),
+            ("long short i;", UnitSpecifier::Short),
+        ] {
+            assert_eq!(
+                decl(s).unwrap_err().data,
+                SemanticError::LongWith(*spec).into(),
+                "wrong error for '{}'",
+                s
+            );
+        }
+        // only one error, and the declaration is still a `long`
+        let mut results = decls("unsigned long char i;");
+        assert_eq!(results.len(), 2);
+        assert!(results.remove(0).is_err());
+        assert!(match_type(results.remove(0), Long(false)));
+    }
+    #[test]
     fn test_arrays() {
         assert!(match_type(
             decl("int a[];"),
@@ -1588,6 +1942,43 @@ pub(crate) mod test {
         }
     }
     #[test]
+    fn test_array_size_error() {
+        let errors = |s| decls(s).into_iter().filter(Result::is_err).count();
+        // a single syntax error, and `a` and `b` are still declared
+        let results = decls("int a[1 +]; int b;");
+        assert_eq!(results.len(), 3);
+        assert_eq!(errors("int a[1 +]; int b;"), 1);
+        // unknown variables in array sizes aren't reported twice
+        assert_eq!(errors("int c[n];"), 1);
+    }
+    #[test]
+    fn test_bool() {
+        assert!(match_type(decl("_Bool b;"), Bool));
+        assert!(match_type(
+            decl("_Bool *p[2];"),
+            Bool.pointer_to(Qualifiers::default())
+                .array_of(ArrayType::Fixed(2))
+        ));
+        // the initializer is converted to `_Bool`, so any nonzero value becomes 1
+        for s in &["_Bool b = 1;", "_Bool b = 2;"] {
+            let b = decl(s).unwrap();
+            assert_eq!(b.symbol.get().ctype, Bool);
+            match b.init {
+                Some(Initializer::Scalar(init)) => assert_eq!(init.ctype, Bool),
+                other => panic!("expected a scalar initializer, got {:?}", other),
+            }
+        }
+        let flag = decl("const _Bool flag;").unwrap().symbol.get();
+        assert_eq!(flag.ctype, Bool);
+        assert!(flag.qualifiers.c_const);
+        for s in &["signed _Bool b;", "unsigned _Bool b;"] {
+            assert_eq!(
+                decl(s).unwrap_err().data,
+                SemanticError::CannotBeSigned(Bool).into()
+            );
+        }
+    }
+    #[test]
     fn test_pointers_and_arrays() {
         // cdecl: declare foo as array 10 of pointer to pointer to char
         assert!(match_type(
@@ -1603,24 +1994,31 @@ pub(crate) mod test {
         // cdecl: declare foo as pointer to pointer to array 10 of int
         assert!(match_type(
             decl("int (**foo)[10];"),
-            Pointer(
-                Box::new(Pointer(
-                    Box::new(Array(Box::new(Int(true)), ArrayType::Fixed(10),)),
-                    Qualifiers::default(),
-                )),
-                Qualifiers::default(),
-            )
+            Int(true)
+                .array_of(ArrayType::Fixed(10))
+                .pointer_to(Qualifiers::default())
+                .pointer_to(Qualifiers::default())
+        ));
+    }
+    #[test]
+    fn test_array_size_literals() {
+        assert!(match_type(
+            decl("int a[0b1000];"),
+            Array(Box::new(Int(true)), ArrayType::Fixed(8))
+        ));
+        assert!(match_type(
+            decl("int b[1'024];"),
+            Array(Box::new(Int(true)), ArrayType::Fixed(1024))
         ));
+        assert!(decl("int c[1''024];").is_err());
     }
     #[test]
     fn test_functions() {
         assert!(match_type(
             decl("void *f();"),
-            Function(FunctionType {
-                return_type: Box::new(Pointer(Box::new(Type::Void), Qualifiers::default())),
-                params: vec![],
-                varargs: false,
-            })
+            Type::Void
+                .pointer_to(Qualifiers::default())
+                .function(vec![], false)
         ));
         // cdecl: declare i as pointer to function returning int;
         assert!(match_type(
@@ -1696,6 +2094,77 @@ pub(crate) mod test {
         assert!(decl("int b[static 10];").is_err());
     }
     #[test]
+    fn test_register_parameter() {
+        let f = decl("int f(register int a[static 10]);").unwrap();
+        match &f.symbol.get().ctype {
+            Function(FunctionType { params, .. }) => {
+                let a = params[0].get();
+                assert_eq!(a.storage_class, StorageClass::Register);
+                assert_eq!(a.ctype, Pointer(Box::new(Int(true)), Qualifiers::default()));
+            }
+            other => panic!("expected a function, got {}", other),
+        }
+        assert_eq!(
+            decl("int f(static int a[static 10]);").unwrap_err().data,
+            SemanticError::ParameterStorageClass(StorageClass::Static).into()
+        );
+        assert!(decl("int f(extern int a);").is_err());
+    }
+    #[test]
+    fn test_parameter_decay() {
+        let param_types = |s| match decl(s).unwrap().symbol.get().ctype.clone() {
+            Function(FunctionType { params, .. }) => params
+                .into_iter()
+                .map(|p| p.get().ctype.clone())
+                .collect::<Vec<_>>(),
+            other => panic!("expected a function, got {}", other),
+        };
+        let char_ptr = Pointer(Box::new(Char(true)), Qualifiers::default());
+        assert_eq!(
+            param_types("int main(int argc, char *argv[]);"),
+            vec![
+                Int(true),
+                Pointer(Box::new(char_ptr), Qualifiers::default())
+            ]
+        );
+        // only the outermost array decays
+        assert_eq!(
+            param_types("void f(int a[][3]);"),
+            vec![Pointer(
+                Box::new(Array(Box::new(Int(true)), ArrayType::Fixed(3))),
+                Qualifiers::default()
+            )]
+        );
+        match &param_types("void f(int a());")[0] {
+            Pointer(inner, _) => assert!(inner.is_function()),
+            other => panic!("expected a function pointer, got {}", other),
+        }
+    }
+    #[test]
+    fn test_qualified_array_parameter() {
+        let param_type = |s| match decl(s).unwrap().symbol.get().ctype.clone() {
+            Function(FunctionType { params, .. }) => params[0].get().ctype.clone(),
+            other => panic!("expected a function, got {}", other),
+        };
+        let const_ptr = Pointer(
+            Box::new(Int(true)),
+            Qualifiers {
+                c_const: true,
+                ..Qualifiers::default()
+            },
+        );
+        assert_eq!(param_type("int f(int a[const]);"), const_ptr);
+        assert_eq!(param_type("int f(int a[const restrict]);"), const_ptr);
+        assert_eq!(param_type("int f(int a[static const 10]);"), const_ptr);
+        // `restrict` is accepted, but not yet tracked by the type system
+        assert_eq!(
+            param_type("int f(int a[restrict]);"),
+            Pointer(Box::new(Int(true)), Qualifiers::default())
+        );
+        // only the array being adjusted can have qualifiers
+        assert!(decl("int a[const];").is_err());
+    }
+    #[test]
     fn test_inline_keyword() {
         // Correct usage
         assert!(match_type(
@@ -1814,6 +2283,79 @@ pub(crate) mod test {
         ));
     }
     #[test]
+    fn test_array_size_enum_constant() {
+        let a = decls("enum { N = 4 }; int a[N];").pop().unwrap().unwrap();
+        assert_eq!(
+            a.symbol.get().ctype,
+            Array(Box::new(Int(true)), ArrayType::Fixed(4))
+        );
+    }
+    #[test]
+    fn test_qualified_typedef() {
+        let symbol = |s: &str| decls(s).pop().unwrap().unwrap().symbol.get();
+
+        let x = symbol("typedef const int T; T x;");
+        assert_eq!(x.ctype, Int(true));
+        assert!(x.qualifiers.c_const);
+        let y = symbol("typedef volatile int V; const V y;");
+        assert!(y.qualifiers.c_const && y.qualifiers.volatile);
+        // the `const` is on the `char`, not on `s`
+        let s = symbol("typedef const char *S; S s;");
+        assert!(!s.qualifiers.c_const);
+        let a = symbol("typedef const char *A[2]; A a;");
+        assert!(!a.qualifiers.c_const);
+        // but an array of `const int` is still const
+        let a = symbol("typedef const int A[2]; A a;");
+        assert!(a.qualifiers.c_const);
+        let m = symbol("typedef const int M[2][2]; M m;");
+        assert!(m.qualifiers.c_const);
+        // the `const` is on the return type, not on the function
+        let f = symbol("typedef const int F(void); F f;");
+        assert!(!f.qualifiers.c_const);
+        assert!(decls("typedef const int F(void); F f;")
+            .into_iter()
+            .all(|decl| decl.is_ok()));
+
+        match decl("register typedef int T;").unwrap_err().data {
+            Error::Semantic(SemanticError::ConflictingStorageClass(..)) => {}
+            other => panic!("expected conflicting storage classes, got {}", other),
+        }
+    }
+    #[test]
+    fn test_typedef_of_pointer_to_function_returning_array_pointer() {
+        // cdecl: declare T as pointer to function (void) returning pointer to array 3 of int
+        let expected = || {
+            Pointer(
+                Box::new(Function(FunctionType {
+                    return_type: Box::new(Pointer(
+                        Box::new(Array(Box::new(Int(true)), ArrayType::Fixed(3))),
+                        Qualifiers::default(),
+                    )),
+                    params: vec![Variable {
+                        ctype: Void,
+                        storage_class: Default::default(),
+                        id: Default::default(),
+                        qualifiers: Default::default(),
+                    }
+                    .insert()],
+                    varargs: false,
+                })),
+                Qualifiers::default(),
+            )
+        };
+        let src = "typedef int (*(*T)(void))[3];";
+        let typedef = decl(src);
+        assert_eq!(
+            typedef.as_ref().unwrap().symbol.get().storage_class,
+            StorageClass::Typedef
+        );
+        assert!(match_type(typedef, expected()));
+        // the typedef keeps the same nesting when it's used
+        let mut parsed = decls(&format!("{} T t;", src));
+        assert_eq!(parsed.len(), 2);
+        assert!(match_type(parsed.remove(1), expected()));
+    }
+    #[test]
     fn test_multiple() {
         assert_same("int i, j, k;", "int i; int j; int k;");
         assert_same(
@@ -1913,6 +2455,211 @@ pub(crate) mod test {
         assert_decl_display("unsigned int i;", &ds.next().unwrap().unwrap().to_string());
     }
     #[test]
+    fn typedef_function_pointer() {
+        let mut ds =
+            decls("typedef void (*handler)(int); handler signal(int, handler);").into_iter();
+        let handler = ds.next().unwrap().unwrap().symbol.get().ctype.clone();
+        match &handler {
+            Pointer(f, _) => assert!(f.is_function()),
+            other => panic!("expected a function pointer, got {}", other),
+        }
+        let signal = ds.next().unwrap().unwrap();
+        match &signal.symbol.get().ctype {
+            Function(FunctionType {
+                return_type,
+                params,
+                varargs: false,
+            }) => {
+                assert_eq!(**return_type, handler);
+                let params: Vec<_> = params.iter().map(|p| p.get().ctype.clone()).collect();
+                assert_eq!(params, vec![Int(true), handler.clone()]);
+            }
+            other => panic!("expected a function, got {}", other),
+        }
+        assert!(ds.next().is_none());
+    }
+    #[test]
+    fn incomplete_array_typedef() {
+        let char_ptr = Pointer(Box::new(Char(true)), Qualifiers::default());
+        let mut ds = decls(
+            "typedef char *argv_t[];
+            argv_t names;
+            argv_t x = {\"a\", \"b\"};
+            argv_t y = {\"c\"};",
+        )
+        .into_iter();
+        let unbounded = Array(Box::new(char_ptr.clone()), ArrayType::Unbounded);
+        assert_eq!(ds.next().unwrap().unwrap().symbol.get().ctype, unbounded);
+        assert_eq!(ds.next().unwrap().unwrap().symbol.get().ctype, unbounded);
+        // the initializer completes the type of the variable, but not of the typedef
+        assert_eq!(
+            ds.next().unwrap().unwrap().symbol.get().ctype,
+            Array(Box::new(char_ptr.clone()), ArrayType::Fixed(2))
+        );
+        assert_eq!(
+            ds.next().unwrap().unwrap().symbol.get().ctype,
+            Array(Box::new(char_ptr), ArrayType::Fixed(1))
+        );
+        assert!(ds.next().is_none());
+    }
+    #[test]
+    fn incomplete_array_without_initializer() {
+        let mut a = Analyzer::new(parser("int a[]; extern int b[];"), false);
+        for decl in &mut a {
+            decl.unwrap();
+        }
+        let warnings: Vec<_> = a.inner.warnings().into_iter().map(|w| w.data).collect();
+        assert_eq!(warnings, vec![Warning::IncompleteArray("a".into())]);
+
+        let err = decls("int f() { int a[]; }")
+            .into_iter()
+            .find_map(Result::err)
+            .unwrap();
+        assert_eq!(err.data, SemanticError::ArraySizeMissing("a".into()).into());
+        assert!(decl("int f() { extern int a[]; }").is_ok());
+    }
+    #[test]
+    fn qualified_function_type() {
+        let err: Error = SemanticError::QualifiedFunctionType.into();
+        let typedef = "typedef int F(void); ";
+        let qualified = |s: &str| {
+            decls(&format!("{}{}", typedef, s))
+                .into_iter()
+                .any(|d| d.map_err(|e| e.data) == Err(err.clone()))
+        };
+        assert!(qualified("const F f;"));
+        assert!(qualified("volatile F *f;"));
+        assert!(!qualified("F *const f;"));
+        assert!(decl("const int f(void);").is_ok());
+        assert!(decl("int (*const f)(void);").is_ok());
+        assert!(decl("int (const f)(void);").is_err());
+    }
+    #[test]
+    fn kr_definition() {
+        let mut a = Analyzer::new(
+            parser("int f(a, b) double b; {}").kr_definitions(true),
+            false,
+        );
+        let f = a.next().unwrap().unwrap();
+        match &f.data.symbol.get().ctype {
+            Function(FunctionType { params, .. }) => {
+                let params: Vec<_> = params.iter().map(|p| p.get().ctype.clone()).collect();
+                assert_eq!(params, vec![Int(true), Double]);
+            }
+            other => panic!("expected a function, got {}", other),
+        }
+        assert!(a.next().is_none());
+        // `a` was never declared, so it defaults to `int`
+        let warnings: Vec<_> = a.inner.warnings().into_iter().map(|w| w.data).collect();
+        assert!(warnings.contains(&Warning::ImplicitInt));
+    }
+    #[test]
+    fn varargs_without_param() {
+        assert_eq!(
+            decl("int f(...);").unwrap_err().data,
+            SemanticError::VarargsWithoutParam.into()
+        );
+        let mut a = Analyzer::new(parser("int f(...);"), false);
+        a.inner.gnu_extensions(true);
+        let f = a.next().unwrap().map(|f| f.data);
+        assert!(match_type(f, Int(true).function(vec![], true)));
+        assert!(a.next().is_none());
+    }
+    #[test]
+    fn auto_type() {
+        let gnu = |s| {
+            let mut a = Analyzer::new(parser(s), false);
+            a.inner.gnu_extensions(true);
+            a.next().unwrap().map(|decl| decl.data)
+        };
+        let ctype = |s| gnu(s).unwrap().symbol.get().ctype.clone();
+        assert_eq!(ctype("__auto_type x = 5;"), Long(true));
+        assert_eq!(ctype("const __auto_type d = 1.5;"), Double);
+
+        for (input, expected) in &[
+            ("__auto_type y;", SemanticError::AutoTypeRequiresInitializer),
+            (
+                "__auto_type *p = 0;",
+                SemanticError::AutoTypeRequiresInitializer,
+            ),
+            (
+                "__auto_type a = { 1 };",
+                SemanticError::AutoTypeRequiresInitializer,
+            ),
+            (
+                "int __auto_type z = 1;",
+                SemanticError::AutoTypeWithType(Int(true)),
+            ),
+        ] {
+            assert_eq!(gnu(*input).unwrap_err().data, expected.clone().into());
+        }
+        // only allowed in GNU mode
+        assert_eq!(
+            decl("__auto_type x = 5;").unwrap_err().data,
+            SemanticError::AutoTypeExtension.into()
+        );
+    }
+    #[test]
+    fn test_char_signedness() {
+        let char_type = |signed, s| {
+            let mut a = Analyzer::new(parser(s), false);
+            a.inner.char_is_signed(signed);
+            a.next().unwrap().unwrap().data.symbol.get().ctype.clone()
+        };
+        assert_eq!(char_type(true, "char c;"), Char(true));
+        assert_eq!(char_type(false, "char c;"), Char(false));
+        // explicit signedness always wins
+        assert_eq!(char_type(false, "signed char c;"), Char(true));
+        assert_eq!(char_type(true, "unsigned char c;"), Char(false));
+        // string literals have the same type as plain `char`
+        let mut a = Analyzer::new(parser("char *s = \"abc\"; char t[] = \"abc\";"), false);
+        a.inner.char_is_signed(false);
+        assert!(a.all(|decl| decl.is_ok()));
+        assert!(a.inner.warnings().is_empty());
+    }
+    #[test]
+    fn pointer_to_incomplete_typedef() {
+        let mut results = decls("typedef struct S T; T *p;");
+        let p = results.pop().unwrap().unwrap();
+        assert!(results.iter().all(Result::is_ok));
+        match &p.symbol.get().ctype {
+            Pointer(inner, _) => assert!(matches!(**inner, Struct(_))),
+            other => panic!("expected a pointer, got {}", other),
+        }
+        // a variable of incomplete type is an error, but only once the scope ends
+        let incomplete = SemanticError::ForwardDeclarationIncomplete("S".into(), "x".into());
+        let results = decls("typedef struct S T; void f(void) { T x; }");
+        assert!(results
+            .iter()
+            .any(|res| matches!(res, Err(err) if err.data == incomplete.clone().into())));
+        // at file scope, the struct could still be completed later; make sure we don't panic
+        decls("typedef struct S T; T x;");
+        assert!(decls("typedef struct S T; T x; struct S { int i; };")
+            .iter()
+            .all(Result::is_ok));
+    }
+    #[test]
+    fn empty_declaration() {
+        let warnings = |s| {
+            let mut a = Analyzer::new(parser(s), false);
+            assert!(a.all(|res| res.is_ok()));
+            a.inner
+                .warnings()
+                .into_iter()
+                .map(|w| w.data)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(warnings("int;"), vec![Warning::EmptyDeclaration]);
+        assert_eq!(
+            warnings("struct { int x; };"),
+            vec![Warning::EmptyDeclaration]
+        );
+        assert!(warnings("struct S;").is_empty());
+        assert!(warnings("union U;").is_empty());
+        assert!(warnings("struct S { int x; };").is_empty());
+        assert!(warnings("enum { A };").is_empty());
+    }
+    #[test]
     fn bitfields() {
         assert!(decl("struct { int:5; } a;").is_err());
         assert!(decl("struct { int a:5; } b;").is_ok());
@@ -1945,10 +2692,66 @@ int main() {
         assert!(decl("int f(int, void);").is_err());
         assert!(decl("int f(void, int);").is_err());
         assert!(decl("int f(void, void);").is_err());
+        assert_eq!(
+            decl("int f(void x);").unwrap_err().data,
+            SemanticError::NamedVoidParameter("x".into()).into()
+        );
+        assert!(decl("int f(int i, void x);").is_err());
+        assert_no_change("extern int f(void *p);");
+        assert_no_change("extern int g(void);");
         assert!(decl("int f(int) { return 1; }").is_err());
         assert_decl_display(
             "int f(void) { return 1; }",
             "extern int f(void) {\n    return (int)(1);\n}\n",
         );
     }
+
+    #[test]
+    fn grouped_declarations() {
+        let mut groups = Analyzer::new(parser("int a, b, c; int d;"), false).grouped();
+        let names = |group: Vec<Locatable<Declaration>>| -> Vec<InternedStr> {
+            group.into_iter().map(|d| d.data.symbol.get().id).collect()
+        };
+        let first = groups.next().unwrap().unwrap();
+        assert_eq!(names(first), vec!["a".into(), "b".into(), "c".into()]);
+        let second = groups.next().unwrap().unwrap();
+        assert_eq!(names(second), vec![InternedStr::from("d")]);
+        assert!(groups.next().is_none());
+    }
+    #[test]
+    fn indexed_declarations() {
+        let decls: Vec<_> = Analyzer::new(parser("int a, b, c; int d;"), false)
+            .indexed()
+            .map(|d| {
+                let (index, decl) = d.unwrap();
+                (index, decl.data.symbol.get().id)
+            })
+            .collect();
+        let (first, second) = (decls[0].0, decls[3].0);
+        assert_ne!(first, second);
+        let expected: Vec<(usize, InternedStr)> = vec![
+            (first, "a".into()),
+            (first, "b".into()),
+            (first, "c".into()),
+            (second, "d".into()),
+        ];
+        assert_eq!(decls, expected);
+    }
+    #[test]
+    fn declaration_hook() {
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let mut analyzer = Analyzer::new(parser("int a, b; int f() { int c; }"), false);
+        let hook_seen = Rc::clone(&seen);
+        analyzer
+            .inner
+            .on_declaration(move |symbol, _| hook_seen.borrow_mut().push(symbol.get().id));
+        for decl in &mut analyzer {
+            decl.unwrap();
+        }
+        let expected: Vec<InternedStr> = vec!["a".into(), "b".into(), "c".into(), "f".into()];
+        assert_eq!(*seen.borrow(), expected);
+    }
 }
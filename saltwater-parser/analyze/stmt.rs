@@ -1,5 +1,11 @@
-use super::FunctionAnalyzer;
-use crate::data::{ast, error::SemanticError, hir::*, lex::Locatable, Location};
+use super::{BreakContext, FunctionAnalyzer};
+use crate::data::{
+    ast,
+    error::{SemanticError, Warning},
+    hir::*,
+    lex::Locatable,
+    Location,
+};
 
 impl FunctionAnalyzer<'_> {
     #[inline(always)]
@@ -42,12 +48,12 @@ impl FunctionAnalyzer<'_> {
                         stmt.location,
                     )
                 }
-                let body = self.parse_stmt(*body);
+                let body = self.nested_stmt(BreakContext::Switch, *body);
                 S::Switch(value, Box::new(body))
             }
             // 6.8.5.2 The do statement
             Do(body, condition) => {
-                let body = self.parse_stmt(*body);
+                let body = self.nested_stmt(BreakContext::Loop, *body);
                 let condition = self
                     .expr(condition)
                     .truthy(&mut self.analyzer.error_handler);
@@ -58,7 +64,7 @@ impl FunctionAnalyzer<'_> {
                 let condition = self
                     .expr(condition)
                     .truthy(&mut self.analyzer.error_handler);
-                let body = self.parse_stmt(*body);
+                let body = self.nested_stmt(BreakContext::Loop, *body);
                 S::While(condition, Box::new(body))
             }
             // 6.8.5.3 The for statement
@@ -75,7 +81,7 @@ impl FunctionAnalyzer<'_> {
                 let condition = condition
                     .map(|e| Box::new(self.expr(*e).truthy(&mut self.analyzer.error_handler)));
                 let post_loop = post_loop.map(|e| Box::new(self.expr(*e)));
-                let body = self.parse_stmt(*body);
+                let body = self.nested_stmt(BreakContext::Loop, *body);
                 self.leave_scope(stmt.location);
                 S::For(Box::new(initializer), condition, post_loop, Box::new(body))
             }
@@ -91,12 +97,35 @@ impl FunctionAnalyzer<'_> {
             // 6.8.6.1 The goto statement
             Goto(label) => S::Goto(label),
             // 6.8.6.2 The continue statement
-            Continue => S::Continue,
+            Continue => {
+                // `continue` inside a switch applies to the loop around it
+                if !self.contexts.contains(&BreakContext::Loop) {
+                    self.err(SemanticError::ContinueOutsideLoop, stmt.location);
+                }
+                S::Continue
+            }
             // 6.8.6.3 The break statement
-            Break => S::Break,
+            Break => {
+                if self.contexts.is_empty() {
+                    self.err(SemanticError::BreakOutsideLoop, stmt.location);
+                }
+                S::Break
+            }
             Return(value) => self.return_statement(value, stmt.location),
             // 6.7 Declarations
             Decl(decls) => S::Decl(self.analyzer.parse_declaration(decls, stmt.location)),
+            // `[[fallthrough]]` has no effect on the generated code
+            Fallthrough => S::default(),
+            // GNU inline assembly
+            Asm { .. } => {
+                self.err(SemanticError::InlineAssembly, stmt.location);
+                S::default()
+            }
+            // _Pragma("once")
+            Pragma(_) => {
+                self.analyzer.warn(Warning::IgnoredPragma, stmt.location);
+                S::default()
+            }
         };
         let data = if !self.analyzer.decl_side_channel.is_empty() {
             let decls = std::mem::replace(&mut self.analyzer.decl_side_channel, Vec::new());
@@ -108,6 +137,13 @@ impl FunctionAnalyzer<'_> {
         };
         Locatable::new(data, stmt.location)
     }
+    /// Analyze the body of a loop or switch, where `break` (and maybe `continue`) is allowed.
+    fn nested_stmt(&mut self, context: BreakContext, body: ast::Stmt) -> Stmt {
+        self.contexts.push(context);
+        let body = self.parse_stmt(body);
+        self.contexts.pop();
+        body
+    }
     // 6.8.1 Labeled statements
     fn case_statement(
         &mut self,
@@ -199,6 +235,7 @@ mod tests {
                     location: Location::default(),
                     return_type: Type::Int(true),
                 },
+                contexts: Vec::new(),
             };
             func_analyzer.parse_stmt(stmt)
         })
@@ -218,4 +255,53 @@ mod tests {
         assert_eq!(parsed, expected);
         assert_eq!(parsed.unwrap().location, expected.unwrap().location);
     }
+    #[test]
+    fn test_block_storage_class() {
+        let stmts = match parse_stmt("{ auto int x = 5; register int y = 3; int z; }")
+            .unwrap()
+            .data
+        {
+            StmtType::Compound(stmts) => stmts,
+            other => panic!("expected a compound statement, got {}", other),
+        };
+        let decls: Vec<_> = stmts
+            .into_iter()
+            .flat_map(|stmt| match stmt.data {
+                StmtType::Decl(decls) => decls,
+                other => panic!("expected a declaration, got {}", other),
+            })
+            .collect();
+        let expected = [
+            (StorageClass::Auto, true),
+            (StorageClass::Register, true),
+            // `auto` is the default at block scope
+            (StorageClass::Auto, false),
+        ];
+        assert_eq!(decls.len(), expected.len());
+        for (decl, &(sc, has_init)) in decls.iter().zip(&expected) {
+            assert_eq!(decl.data.symbol.get().storage_class, sc);
+            assert_eq!(decl.data.init.is_some(), has_init);
+        }
+    }
+    #[test]
+    fn test_break_and_continue() {
+        let err = |s| parse_stmt(s).unwrap_err().data;
+
+        assert!(parse_stmt("for (;;) { break; }").is_ok());
+        assert!(parse_stmt("for (;;) continue;").is_ok());
+        assert!(parse_stmt("do { if (1) break; else continue; } while (1);").is_ok());
+        assert!(parse_stmt("switch (1) { case 1: break; }").is_ok());
+        // `continue` goes to the loop, not the switch
+        assert!(parse_stmt("while (1) switch (1) { default: continue; }").is_ok());
+
+        assert_eq!(
+            err("{ for (;;) { break; } break; }"),
+            SemanticError::BreakOutsideLoop.into()
+        );
+        assert_eq!(err("continue;"), SemanticError::ContinueOutsideLoop.into());
+        assert_eq!(
+            err("switch (1) { default: continue; }"),
+            SemanticError::ContinueOutsideLoop.into()
+        );
+    }
 }
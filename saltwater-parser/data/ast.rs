@@ -2,7 +2,7 @@ use std::fmt::{self, Display};
 
 use super::{joined, joined_locatable};
 use crate::data::hir::LiteralValue;
-use crate::data::lex::{AssignmentToken, ComparisonToken, Locatable};
+use crate::data::lex::{AssignmentToken, ComparisonToken, Locatable, Token};
 use crate::intern::InternedStr;
 
 pub type Program = Vec<Declaration>;
@@ -19,6 +19,10 @@ pub struct FunctionDefinition {
     pub id: InternedStr,
     pub declarator: FunctionDeclarator,
     pub body: CompoundStatement,
+    /// The comment immediately before this function, if comments were preserved
+    pub doc: Option<String>,
+    /// Standard attributes, such as `[[noreturn]]`
+    pub attributes: Vec<Locatable<Attribute>>,
 }
 
 impl FunctionDefinition {
@@ -72,6 +76,7 @@ pub enum UnitSpecifier {
     Complex,
     Imaginary,
     VaList,
+    AutoType,
 
     // qualifiers
     Const,
@@ -123,6 +128,24 @@ pub struct StructDeclarator {
 pub struct Declaration {
     pub specifiers: Vec<DeclarationSpecifier>,
     pub declarators: Vec<Locatable<InitDeclarator>>,
+    /// The comment immediately before this declaration, if comments were preserved
+    pub doc: Option<String>,
+    /// Standard attributes, such as `[[deprecated]]`
+    pub attributes: Vec<Locatable<Attribute>>,
+}
+
+/// A standard attribute, like `[[nodiscard]]`.
+///
+/// Attributes which aren't in the C standard are ignored by the parser.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Attribute {
+    NoReturn,
+    /// `[[deprecated]]` or `[[deprecated("reason")]]`
+    Deprecated(Option<String>),
+    MaybeUnused,
+    Fallthrough,
+    /// `[[nodiscard]]` or `[[nodiscard("reason")]]`
+    NoDiscard(Option<String>),
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -135,6 +158,18 @@ pub struct InitDeclarator {
 pub enum Initializer {
     Scalar(Box<Expr>),
     Aggregate(Vec<Initializer>),
+    /// `.x = 1` or `[0] = 1`, only allowed as an element of an `Aggregate`
+    Designated(Vec<Locatable<Designator>>, Box<Initializer>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Designator {
+    /// `[0]`
+    Index(Expr),
+    /// `.x`, or `x:` in GNU C
+    Field(InternedStr),
+    /// `[0 ... 3]`, a GNU extension
+    Range(Expr, Expr),
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -146,8 +181,7 @@ pub struct Declarator {
 #[derive(Clone, Debug, PartialEq)]
 pub struct FunctionDeclarator {
     pub return_type: Box<DeclaratorType>,
-    // TODO: maybe support K&R C?
-    //DeclarationList
+    /// For old-style (K&R) definitions, this includes the types from the declaration list
     pub params: Vec<TypeName>,
     pub varargs: bool,
 }
@@ -169,6 +203,8 @@ pub enum DeclaratorType {
     Array {
         of: Box<DeclaratorType>,
         size: Option<Box<Expr>>,
+        /// `int f(int a[const])`, only allowed for parameters
+        qualifiers: Vec<DeclarationSpecifier>,
     },
     Function(FunctionDeclarator),
 }
@@ -201,15 +237,44 @@ pub enum StmtType {
     Break,
     Return(Option<Expr>),
     Decl(Declaration),
+    // [[fallthrough]];
+    Fallthrough,
+    // asm volatile ("mov %1, %0" : "=r"(dst) : "r"(src) : "memory");
+    Asm {
+        template: String,
+        volatile: bool,
+        outputs: Vec<AsmOperand>,
+        inputs: Vec<AsmOperand>,
+        clobbers: Vec<String>,
+    },
+    // _Pragma("once")
+    Pragma(String),
+}
+
+/// An input or output of an `asm` statement, like `"=r"(dst)`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AsmOperand {
+    pub constraint: String,
+    pub expr: Expr,
 }
 
 pub type Expr = Locatable<ExprType>;
 
+/// One of the choices in a `_Generic` selection, like `int: 1` or `default: 0`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GenericAssociation {
+    /// `None` for the `default` association
+    pub type_name: Option<TypeName>,
+    pub expr: Expr,
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub enum ExprType {
     // primary
     Id(InternedStr),
     Literal(LiteralValue),
+    // GNU extension: `1.0i`
+    Imaginary(f64),
 
     // postfix
     FuncCall(Box<Expr>, Vec<Expr>),
@@ -225,6 +290,12 @@ pub enum ExprType {
     Cast(TypeName, Box<Expr>),
     AlignofType(TypeName),
     AlignofExpr(Box<Expr>),
+    // GNU extension: `__builtin_types_compatible_p(int, unsigned)`
+    TypesCompatible(Box<TypeName>, Box<TypeName>),
+    // GNU extension: `__builtin_choose_expr(constant, if_true, if_false)`
+    ChooseExpr(Box<Expr>, Box<Expr>, Box<Expr>),
+    // _Generic(x, int: 1, default: 0)
+    Generic(Box<Expr>, Vec<GenericAssociation>),
     SizeofType(TypeName),
     SizeofExpr(Box<Expr>),
     Deref(Box<Expr>),
@@ -256,6 +327,11 @@ pub enum ExprType {
     // Ternary: if ? then : else
     Ternary(Box<Expr>, Box<Expr>, Box<Expr>),
     Comma(Box<Expr>, Box<Expr>),
+
+    /// An expression that failed to parse.
+    ///
+    /// The error has already been reported, so this should not cause any more errors.
+    Error,
 }
 
 impl Default for StmtType {
@@ -311,6 +387,9 @@ impl Display for ExternalDeclaration {
 
 impl Display for FunctionDefinition {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for attr in &self.attributes {
+            write!(f, "{} ", attr.data)?;
+        }
         for spec in &self.specifiers {
             write!(f, "{} ", spec)?;
         }
@@ -321,6 +400,9 @@ impl Display for FunctionDefinition {
 
 impl Display for Declaration {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for attr in &self.attributes {
+            write!(f, "{} ", attr.data)?;
+        }
         let specs = joined(&self.specifiers, " ");
         write!(f, "{}", specs)?;
         if !specs.is_empty() {
@@ -330,6 +412,22 @@ impl Display for Declaration {
     }
 }
 
+impl Display for Attribute {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (name, reason) = match self {
+            Attribute::NoReturn => ("noreturn", None),
+            Attribute::Deprecated(reason) => ("deprecated", reason.as_ref()),
+            Attribute::MaybeUnused => ("maybe_unused", None),
+            Attribute::Fallthrough => ("fallthrough", None),
+            Attribute::NoDiscard(reason) => ("nodiscard", reason.as_ref()),
+        };
+        match reason {
+            Some(reason) => write!(f, "[[{}({:?})]]", name, reason),
+            None => write!(f, "[[{}]]", name),
+        }
+    }
+}
+
 impl Display for InitDeclarator {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{}", self.declarator)?;
@@ -349,6 +447,22 @@ impl Display for Initializer {
                 write!(f, "{}", joined(items, ", "))?;
                 write!(f, " }}")
             }
+            Initializer::Designated(designators, init) => {
+                for designator in designators {
+                    write!(f, "{}", designator.data)?;
+                }
+                write!(f, " = {}", init)
+            }
+        }
+    }
+}
+
+impl Display for Designator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Designator::Index(index) => write!(f, "[{}]", index),
+            Designator::Field(name) => write!(f, ".{}", name),
+            Designator::Range(low, high) => write!(f, "[{} ... {}]", low, high),
         }
     }
 }
@@ -422,6 +536,7 @@ impl Display for UnitSpecifier {
             Complex => write!(f, "_Complex"),
             Imaginary => write!(f, "_Imaginary"),
             VaList => write!(f, "va_list"),
+            AutoType => write!(f, "__auto_type"),
         }
     }
 }
@@ -473,12 +588,17 @@ impl DeclaratorType {
         }
         for declarator_type in unrolled_type[..unrolled_type.len() - 1].iter() {
             match declarator_type {
-                DeclaratorType::Array { size, .. } => {
+                DeclaratorType::Array {
+                    size, qualifiers, ..
+                } => {
+                    write!(f, "[{}", joined(qualifiers, " "))?;
                     if let Some(size) = size {
-                        write!(f, "[{}]", size)?;
-                    } else {
-                        write!(f, "[]")?;
+                        if !qualifiers.is_empty() {
+                            write!(f, " ")?;
+                        }
+                        write!(f, "{}", size)?;
                     }
+                    write!(f, "]")?;
                 }
                 DeclaratorType::Function(function_declarator) => {
                     write!(f, "({}", joined(function_declarator.params.iter(), ", "))?;
@@ -511,6 +631,16 @@ impl Display for FunctionDeclarator {
 }
 
 impl FunctionDeclarator {
+    /// Whether the parameters are only names, as in an old-style definition: `int f(a, b)`
+    pub fn is_identifier_list(&self) -> bool {
+        !self.params.is_empty()
+            && !self.varargs
+            && self.params.iter().all(|param| {
+                param.specifiers.is_empty()
+                    && param.declarator.decl == DeclaratorType::End
+                    && param.declarator.id.is_some()
+            })
+    }
     fn pretty_print(&self, name: Option<InternedStr>, f: &mut fmt::Formatter) -> fmt::Result {
         // TODO: maybe factor out some of the repeated code?
         // print_pre
@@ -615,8 +745,41 @@ impl StmtType {
                 body.data.pretty_print(f, depth)
             }
             StmtType::Decl(decls) => write!(f, "{}", decls),
+            StmtType::Fallthrough => write!(f, "[[fallthrough]];"),
+            StmtType::Pragma(pragma) => write!(f, "{}", Token::Pragma(pragma.clone())),
             StmtType::Compound(stmts) => pretty_print_compound(f, stmts, depth),
             StmtType::Switch(condition, body) => write!(f, "switch ({}) {}", condition, body.data),
+            StmtType::Asm {
+                template,
+                volatile,
+                outputs,
+                inputs,
+                clobbers,
+            } => {
+                write!(f, "asm ")?;
+                if *volatile {
+                    write!(f, "volatile ")?;
+                }
+                write!(f, "({:?}", template)?;
+                if !(outputs.is_empty() && inputs.is_empty() && clobbers.is_empty()) {
+                    let operands = |operands: &[AsmOperand]| {
+                        operands
+                            .iter()
+                            .map(|op| format!("{:?}({})", op.constraint, op.expr))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    };
+                    let clobbers: Vec<_> = clobbers.iter().map(|c| format!("{:?}", c)).collect();
+                    write!(
+                        f,
+                        " : {} : {} : {}",
+                        operands(outputs),
+                        operands(inputs),
+                        clobbers.join(", ")
+                    )?;
+                }
+                write!(f, ");")
+            }
         }
     }
 }
@@ -626,6 +789,7 @@ impl Display for Expr {
         match &self.data {
             ExprType::Comma(left, right) => write!(f, "{}, {}", *left, *right),
             ExprType::Literal(token) => write!(f, "{}", token),
+            ExprType::Imaginary(value) => write!(f, "{}i", value),
             ExprType::Id(symbol) => write!(f, "{}", symbol),
             ExprType::Add(left, right) => write!(f, "({}) + ({})", left, right),
             ExprType::Sub(left, right) => write!(f, "({}) - ({})", left, right),
@@ -640,6 +804,7 @@ impl Display for Expr {
             ExprType::Negate(expr) => write!(f, "-({})", expr),
             ExprType::UnaryPlus(expr) => write!(f, "+({})", expr),
             ExprType::LogicalNot(expr) => write!(f, "!({})", expr),
+            ExprType::Error => write!(f, "<error>"),
             ExprType::LogicalOr(left, right) => write!(f, "({}) || ({})", left, right),
             ExprType::LogicalAnd(left, right) => write!(f, "({}) && ({})", left, right),
             ExprType::Shift(val, by, left) => {
@@ -667,6 +832,22 @@ impl Display for Expr {
             ExprType::SizeofType(ty) => write!(f, "sizeof({})", ty),
             ExprType::AlignofExpr(expr) => write!(f, "alignof({})", expr),
             ExprType::AlignofType(ty) => write!(f, "alignof({})", ty),
+            ExprType::TypesCompatible(left, right) => {
+                write!(f, "__builtin_types_compatible_p({}, {})", left, right)
+            }
+            ExprType::ChooseExpr(cond, left, right) => {
+                write!(f, "__builtin_choose_expr({}, {}, {})", cond, left, right)
+            }
+            ExprType::Generic(controlling, associations) => {
+                write!(f, "_Generic({}", controlling)?;
+                for assoc in associations {
+                    match &assoc.type_name {
+                        Some(type_name) => write!(f, ", {}: {}", type_name, assoc.expr)?,
+                        None => write!(f, ", default: {}", assoc.expr)?,
+                    }
+                }
+                write!(f, ")")
+            }
         }
     }
 }
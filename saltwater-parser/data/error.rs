@@ -31,7 +31,7 @@ impl<T> Default for ErrorHandler<T> {
     }
 }
 
-impl<T> ErrorHandler<T> {
+impl<T: PartialEq> ErrorHandler<T> {
     /// Construct a new error handler.
     pub(crate) fn new() -> ErrorHandler<T> {
         Default::default()
@@ -43,8 +43,18 @@ impl<T> ErrorHandler<T> {
     }
 
     /// Add an error to the error handler.
+    ///
+    /// Errors identical to one still in the queue at the same location are dropped:
+    /// recovery can easily hit the same failure more than once.
     pub(crate) fn push_back<E: Into<Locatable<T>>>(&mut self, error: E) {
-        self.errors.push_back(error.into());
+        let error = error.into();
+        let seen = self
+            .errors
+            .iter()
+            .any(|e| e.location == error.location && e.data == error.data);
+        if !seen {
+            self.errors.push_back(error);
+        }
     }
 
     /// Remove the first error from the queue
@@ -59,12 +69,14 @@ impl<T> ErrorHandler<T> {
 
     /// Shortcut for adding an error
     pub(crate) fn error<E: Into<T>>(&mut self, error: E, location: Location) {
-        self.errors.push_back(location.with(error.into()));
+        self.push_back(location.with(error.into()));
     }
 
     /// Add an iterator of errors to the error queue
     pub(crate) fn extend<E: Into<Locatable<T>>>(&mut self, iter: impl Iterator<Item = E>) {
-        self.errors.extend(iter.map(Into::into));
+        for error in iter {
+            self.push_back(error);
+        }
     }
 
     /// Move another `ErrorHandler`'s errors and warnings into this one.
@@ -72,8 +84,7 @@ impl<T> ErrorHandler<T> {
     where
         T: From<S>,
     {
-        self.errors
-            .extend(&mut other.errors.drain(..).map(|loc| loc.map(Into::into)));
+        self.extend(other.errors.drain(..).map(|loc| loc.map(Into::into)));
         self.warnings.append(&mut other.warnings);
     }
 }
@@ -99,6 +110,10 @@ pub enum Error {
 
     #[error("invalid token: {0}")]
     Lex(#[from] LexError),
+
+    /// A warning, when warnings are being treated as errors
+    #[error("{0} [-Werror]")]
+    Warning(Warning),
 }
 
 /// Semantic errors are non-exhaustive and may have new variants added at any time
@@ -113,6 +128,8 @@ pub enum SemanticError {
     InvalidSpecifier {
         existing: ast::DeclarationSpecifier,
         new: ast::DeclarationSpecifier,
+        /// where `existing` was specified
+        previous: Location,
     },
 
     #[error("'{0}' is not a qualifier and cannot be used for pointers")]
@@ -127,9 +144,21 @@ pub enum SemanticError {
     #[error("conflicting types '{0}' and '{1}'")]
     ConflictingType(Type, Type),
 
+    #[error("a type cannot be both 'long' and '{0}'")]
+    LongWith(ast::UnitSpecifier),
+
     #[error("'{0}' cannot be signed or unsigned")]
     CannotBeSigned(Type),
 
+    #[error("'__auto_type' is a GNU extension")]
+    AutoTypeExtension,
+
+    #[error("'__auto_type' cannot be combined with '{0}'")]
+    AutoTypeWithType(Type),
+
+    #[error("'__auto_type' can only declare a single variable with a scalar initializer")]
+    AutoTypeRequiresInitializer,
+
     #[error("types cannot be both signed and unsigned")]
     ConflictingSigned,
 
@@ -146,7 +175,7 @@ pub enum SemanticError {
     #[error("arrays must have a positive length")]
     NegativeLength,
 
-    #[error("function parameters always have a storage class of `auto`")]
+    #[error("invalid storage class `{0}` for function parameter, only `register` is allowed")]
     ParameterStorageClass(StorageClass),
 
     #[error("duplicate parameter name '{0}' in function declaration")]
@@ -162,6 +191,9 @@ pub enum SemanticError {
     #[error("void must be the first and only parameter if specified")]
     InvalidVoidParameter,
 
+    #[error("parameter '{0}' has incomplete type 'void'")]
+    NamedVoidParameter(InternedStr),
+
     #[error("functions taking `void` must not have variadic arguments")]
     VoidVarargs,
 
@@ -174,10 +206,34 @@ pub enum SemanticError {
     #[error("variable has incomplete type 'void'")]
     VoidType,
 
+    #[error("'break' statement not in loop or switch statement")]
+    BreakOutsideLoop,
+
+    #[error("'continue' statement not in loop")]
+    ContinueOutsideLoop,
+
+    #[error("restrict requires a pointer type, got '{0}'")]
+    RestrictWithoutPointer(Type),
+
+    #[error("imaginary constants are a GNU extension")]
+    ImaginaryConstant,
+
+    #[error("complex numbers are not supported")]
+    ComplexNotSupported,
+
+    #[error("'{0}' is a GNU extension")]
+    GnuBuiltin(&'static str),
+
+    #[error("inline assembly is not supported")]
+    InlineAssembly,
+
     // expression errors
     #[error("use of undeclared identifier '{0}'")]
     UndeclaredVar(InternedStr),
 
+    #[error("implicit declaration of function '{0}' is not allowed since C99")]
+    ImplicitFunctionDeclaration(InternedStr),
+
     #[error("expected expression, got typedef")]
     TypedefInExpressionContext,
 
@@ -265,6 +321,12 @@ pub enum SemanticError {
     #[error("not a constant expression: {0}")]
     NotConstant(Expr),
 
+    #[error("controlling expression type '{0}' not compatible with any association in _Generic")]
+    NoGenericAssociation(Type),
+
+    #[error("duplicate association for {} in _Generic", .0.as_ref().map_or("default".into(), |t| format!("'{}'", t)))]
+    DuplicateGenericAssociation(Option<Type>),
+
     #[error("cannot dereference NULL pointer")]
     NullPointerDereference,
 
@@ -313,6 +375,21 @@ pub enum SemanticError {
     #[error("too many initializers (declared with {0} elements, found {1})")]
     TooManyMembers(usize, usize),
 
+    #[error("union initializers may only have one element (initialized with {0})")]
+    TooManyUnionInitializers(usize),
+
+    #[error("array designator used to initialize non-array type '{0}'")]
+    ArrayDesignatorNonArray(Type),
+
+    #[error("array designator index {0} is negative")]
+    NegativeDesignator(i64),
+
+    #[error("array designator index {0} is out of bounds for an array of {1} elements")]
+    DesignatorOutOfBounds(u64, u64),
+
+    #[error("empty range in designator: {0} is greater than {1}")]
+    EmptyDesignatorRange(u64, u64),
+
     // Function definition errors
     #[error("illegal storage class {0} for function (only `static` and `extern` are allowed)")]
     InvalidFuncStorageClass(StorageClass),
@@ -336,6 +413,15 @@ pub enum SemanticError {
     #[error("'{0}' can only appear on functions")]
     FuncQualifiersNotAllowed(hir::FunctionQualifiers),
 
+    #[error("function type may not be qualified")]
+    QualifiedFunctionType,
+
+    #[error("function '{0}' is initialized like a variable")]
+    FunctionInitializer(InternedStr),
+
+    #[error("array size missing in '{0}'")]
+    ArraySizeMissing(InternedStr),
+
     // stmt errors
     // new with the new parser
     #[error("switch expressions must have an integer type (got {0})")]
@@ -358,13 +444,22 @@ pub enum SyntaxError {
     #[error("expected {0}, got <end-of-file>")]
     EndOfFile(&'static str),
 
+    #[error("unexpected '{0}' after the end of the statement")]
+    TrailingTokens(Token),
+
     #[error("expected statement, got {0}")]
     NotAStatement(super::Keyword),
 
+    #[error("expected string literal")]
+    ExpectedString,
+
     // expected a primary expression, but got EOF or an invalid token
     #[error("expected variable, literal, or '('")]
     MissingPrimary,
 
+    #[error("expected at least one association")]
+    EmptyGeneric,
+
     #[error("expected identifier, got '{}'",
         .0.as_ref().map_or("<end-of-file>".into(),
                            |t| std::borrow::Cow::Owned(t.to_string())))]
@@ -376,11 +471,19 @@ pub enum SyntaxError {
     #[error("expected declarator in declaration")]
     ExpectedDeclarator,
 
+    #[error("old-style (K&R) function definitions are not supported")]
+    KRDefinition,
+
+    #[error("declaration for parameter '{0}' but no such parameter")]
+    NoSuchParameter(InternedStr),
+
     #[error("empty type name")]
     ExpectedType,
 
-    #[error("expected '(', '*', or variable, got '{0}'")]
-    ExpectedDeclaratorStart(Token),
+    #[error("expected one of {}; got '{}'", display_choices(.0),
+        .1.as_ref().map_or("<end-of-file>".into(),
+                           |t| std::borrow::Cow::Owned(t.to_string())))]
+    ExpectedOneOf(Vec<Token>, Option<Token>),
 
     #[error("only functions can have a function body (got {0})")]
     NotAFunction(ast::InitDeclarator),
@@ -389,14 +492,53 @@ pub enum SyntaxError {
     FunctionInitializer(ast::Initializer),
 
     #[error("function not allowed in this context (got {})", .0.as_type())]
-    FunctionNotAllowed(ast::FunctionDefinition),
+    FunctionNotAllowed(Box<ast::FunctionDefinition>),
 
     #[error("function definitions must have a name")]
     MissingFunctionName,
 
+    #[error("function definition is not allowed here")]
+    FunctionDefinitionNotAllowed,
+
+    #[error("declarations are not allowed in the condition of '{0}' (this is only valid in C++)")]
+    DeclarationInCondition(Keyword),
+
+    #[error("{0} attribute {1}")]
+    MisplacedAttribute(ast::Attribute, &'static str),
+
+    #[error("storage class specifier not allowed here")]
+    StorageClassInDeclarator(Keyword),
+
+    #[error("typedef requires a name")]
+    TypedefWithoutName,
+
+    #[error("expected ';' after declaration")]
+    MissingSemicolon,
+
+    #[error("range designators are a GNU extension")]
+    RangeDesignator,
+
+    #[error("'{0}:' designator is a GNU extension, use '.{0} =' instead")]
+    ObsoleteDesignator(InternedStr),
+
+    #[error("'={0}' is not an operator, did you mean '{0}='?")]
+    TransposedOperator(lex::ComparisonToken),
+
+    #[error("trailing comma in function call arguments")]
+    TrailingCommaInCall,
+
+    #[error("functions cannot return arrays")]
+    FunctionReturningArray,
+
     #[error("`static` for array sizes is only allowed in function declarations")]
     StaticInConcreteArray,
 
+    #[error("type qualifiers in array sizes are only allowed in function declarations")]
+    QualifierInConcreteArray,
+
+    #[error("'_Alignof' can only be applied to a type name, use '__alignof__' for an expression")]
+    AlignofExpr,
+
     #[error("overflow while parsing {}integer literal",
         if let Some(signed) = .is_signed {
             if *signed { "signed "} else { "unsigned "}
@@ -429,6 +571,10 @@ pub enum CppError {
     #[error("invalid preprocessing directive")]
     InvalidDirective,
 
+    /// A directive was present in input that was marked as already preprocessed
+    #[error("preprocessing directives are not allowed in preprocessed input")]
+    DirectiveInPreprocessedInput,
+
     /// A valid token was present in an invalid position, such as `#if *`
     ///
     /// The `&str` describes the expected token;
@@ -541,6 +687,9 @@ pub enum LexError {
     #[error("invalid digit {digit} in {radix} constant")]
     InvalidDigit { digit: u32, radix: Radix },
 
+    #[error("digit separator must be between two digits")]
+    MisplacedDigitSeparator,
+
     #[error("multi-byte character literal")]
     MultiByteCharLiteral,
 
@@ -575,15 +724,24 @@ pub enum Warning {
             if *.1 > 1 { format!(" occurs {} times", .1) } else { String::new() })]
     DuplicateSpecifier(ast::UnitSpecifier, usize),
 
+    #[error("{0} redundant qualifiers in this declaration, consider removing the duplicates")]
+    RedundantQualifiers(usize),
+
     #[error("qualifiers in type casts are ignored")]
     IgnoredQualifier(hir::Qualifiers),
 
     #[error("declaration does not declare anything")]
     EmptyDeclaration,
 
+    #[error("implicit declaration of function '{0}'")]
+    ImplicitFunctionDeclaration(InternedStr),
+
     #[error("{} does not support #pragma", env!("CARGO_PKG_NAME"))]
     IgnoredPragma,
 
+    #[error("{} does not support attributes", env!("CARGO_PKG_NAME"))]
+    IgnoredAttribute,
+
     #[error("variadic macros are not yet supported")]
     IgnoredVariadic,
 
@@ -592,6 +750,24 @@ pub enum Warning {
 
     #[error("this is a definition, not a declaration, the 'extern' keyword has no effect")]
     ExtraneousExtern,
+
+    #[error("array '{0}' has no size and must be completed by a later declaration")]
+    IncompleteArray(InternedStr),
+
+    #[error("'=!' assigns the negation of the right-hand side, did you mean '!='?")]
+    AssignNegation,
+
+    #[error("using the result of an assignment as a condition, did you mean '=='? add parentheses to silence this warning")]
+    AssignmentInCondition,
+
+    #[error("ISO C90 forbids mixing declarations and code")]
+    MixedDeclarations,
+
+    #[error("label at end of compound statement is a C23 extension")]
+    LabelAtEndOfBlock,
+
+    #[error("use of GNU old-style field designator, use '.{0} =' instead")]
+    ObsoleteDesignator(InternedStr),
 }
 
 impl<T: Into<String>> From<T> for Warning {
@@ -616,6 +792,17 @@ impl CompileError {
 }
 
 impl Error {
+    /// Another location relevant to this error, with a message explaining why.
+    ///
+    /// For example, `int int i;` points to the first `int` as well as the second.
+    pub fn note(&self) -> Option<(String, Location)> {
+        match self {
+            Error::Semantic(SemanticError::InvalidSpecifier {
+                existing, previous, ..
+            }) => Some((format!("'{}' first specified here", existing), *previous)),
+            _ => None,
+        }
+    }
     pub fn is_lex_err(&self) -> bool {
         if let Error::Lex(_) = self {
             true
@@ -681,6 +868,19 @@ impl<S: Into<String>> From<S> for SemanticError {
     }
 }
 
+/// `'(', '*', identifier`
+fn display_choices(choices: &[Token]) -> String {
+    choices
+        .iter()
+        .map(|choice| match choice {
+            // identifiers match by kind, so the name isn't meaningful
+            Token::Id(_) => "identifier".to_string(),
+            _ => format!("'{}'", choice),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -697,6 +897,23 @@ mod tests {
         assert_eq!(errors.len(), 1);
     }
 
+    #[test]
+    fn test_error_handler_dedup() {
+        let mut error_handler = ErrorHandler::new();
+        error_handler.push_back(dummy_error());
+        error_handler.push_back(dummy_error());
+        error_handler.error(LexError::UnterminatedComment, Location::default());
+        assert_eq!(error_handler.clone().count(), 1);
+
+        // same error, different location
+        let mut other = dummy_error();
+        other.location.span = (1..2).into();
+        error_handler.push_back(other);
+        // same location, different error
+        error_handler.error(LexError::NoNewlineAtEOF, Location::default());
+        assert_eq!(error_handler.by_ref().count(), 3);
+    }
+
     #[test]
     fn test_compile_error_is_kind() {
         let e = Error::Lex(LexError::UnterminatedComment);
@@ -53,6 +53,7 @@ pub struct Declaration {
 pub enum Initializer {
     Scalar(Box<Expr>),                 // int i = 5;
     InitializerList(Vec<Initializer>), // int a[] = { 1, 2, 3 };
+    Union(usize, Box<Initializer>),    // union { int i; float f; } u = { .f = 1 };
     FunctionBody(Vec<Stmt>),           // int f() { return 0; }
 }
 
@@ -399,6 +400,8 @@ impl Display for Initializer {
                 write!(f, "{}", joined(list, ", "),)?;
                 write!(f, " }}")
             }
+            // the member is only known from the type, see `Declaration`
+            Initializer::Union(_, init) => write!(f, "{{ {} }}", init),
             Initializer::FunctionBody(body) => {
                 writeln!(f, "{{")?;
                 for stmt in body {
@@ -514,6 +517,12 @@ impl Display for Declaration {
                 }
                 write!(f, "}};")
             }
+            Some(Initializer::Union(member, init)) => match &self.symbol.get().ctype {
+                Type::Union(stype) => {
+                    write!(f, " = {{.{} = {}}};", stype.members()[*member].id, init)
+                }
+                _ => write!(f, " = {{{}}};", init),
+            },
             None => write!(f, ";"),
         }
     }
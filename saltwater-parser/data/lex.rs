@@ -67,6 +67,23 @@ impl<T> Locatable<T> {
             location: self.location,
         }
     }
+    /// Like `map`, but `f` can fail. The location is kept whether or not it succeeds.
+    pub fn and_then<S, E, F: FnOnce(T) -> Result<S, E>>(
+        self,
+        f: F,
+    ) -> Result<Locatable<S>, Locatable<E>> {
+        match f(self.data) {
+            Ok(data) => Ok(self.location.with(data)),
+            Err(err) => Err(self.location.with(err)),
+        }
+    }
+}
+
+impl<T, E> Locatable<Result<T, E>> {
+    /// Move the location inside the `Result`, converting the error with `f`.
+    pub fn map_err<F, O: FnOnce(E) -> F>(self, op: O) -> Result<Locatable<T>, Locatable<F>> {
+        self.and_then(|res| res.map_err(op))
+    }
 }
 
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
@@ -110,6 +127,8 @@ pub enum Keyword {
     Complex,
     Imaginary,
     VaList,
+    // GNU extension: `__auto_type x = 1;`
+    AutoType,
 
     // qualifiers
     Const,
@@ -134,9 +153,15 @@ pub enum Keyword {
     StaticAssert,
     Alignas,
     Alignof,
+    // GNU builtins
+    // `__alignof__`, which unlike `_Alignof` can be applied to an expression
+    GnuAlignof,
+    TypesCompatible,
+    ChooseExpr,
+    Asm,
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 #[cfg_attr(test, derive(Arbitrary))]
 pub enum AssignmentToken {
     Equal,
@@ -152,7 +177,7 @@ pub enum AssignmentToken {
     XorEqual, // ^=
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 #[cfg_attr(test, derive(Arbitrary))]
 pub enum ComparisonToken {
     Less,
@@ -166,9 +191,13 @@ pub enum ComparisonToken {
 #[derive(Clone, Debug)]
 pub enum LiteralToken {
     // literals
+    /// The full spelling of the literal, including any `l` or `ll` suffix
     Int(Substr),
+    /// The full spelling of a literal with a `u` suffix, e.g. `0xFFul`
     UnsignedInt(Substr),
     Float(Substr),
+    /// GNU extension: the full spelling of a floating constant with an `i` or `j` suffix, e.g. `1.0i`
+    Imaginary(Substr),
     Str(Vec<Substr>),
     Char(Substr),
 }
@@ -180,6 +209,7 @@ impl PartialEq for LiteralToken {
             (Int(x), Int(y))
             | (UnsignedInt(x), UnsignedInt(y))
             | (Float(x), Float(y))
+            | (Imaginary(x), Imaginary(y))
             | (Char(x), Char(y)) => x.as_str() == y.as_str(),
             (Str(x), Str(y)) => x.iter().zip(y).all(|(x, y)| x.as_str() == y.as_str()),
             _ => false,
@@ -233,6 +263,60 @@ pub enum Token {
     Ellipsis,
     StructDeref, // ->
     Hash,        // #, used for preprocessing
+    /// `_Pragma("once")`, with the string literal already destringized
+    Pragma(String),
+}
+
+/// The category of a `Token`, ignoring any data it carries.
+///
+/// Keywords, assignments, and comparisons keep their payload,
+/// since the parser almost always wants to match them exactly.
+/// Literals, identifiers, and whitespace do not,
+/// so `TokenKind::Id` matches any identifier.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum TokenKind {
+    PlusPlus,
+    MinusMinus,
+    Assignment(AssignmentToken),
+    Comparison(ComparisonToken),
+
+    Plus,
+    Minus,
+    Star,
+    Divide,
+    Mod,
+    Xor,
+    Ampersand,
+    LogicalAnd,
+    BitwiseOr,
+    LogicalOr,
+    BinaryNot,
+    LogicalNot,
+    ShiftRight,
+    ShiftLeft,
+
+    LeftBrace,
+    RightBrace,
+    LeftBracket,
+    RightBracket,
+    LeftParen,
+    RightParen,
+    Semicolon,
+    Colon,
+    Comma,
+    Dot,
+    Question,
+
+    Keyword(Keyword),
+    Literal,
+    Id,
+
+    Whitespace,
+
+    Ellipsis,
+    StructDeref,
+    Hash,
+    Pragma,
 }
 
 /* impls */
@@ -280,6 +364,40 @@ impl Location {
     pub fn is_empty(&self) -> bool {
         self.span.is_empty()
     }
+
+    /// Return the line and column where this location starts, counting from `base`.
+    ///
+    /// codespan counts from 0, but most editors count from 1.
+    /// Tabs advance the column to the next multiple of `tab_width`.
+    /// Returns `None` if this location does not belong to `files`.
+    pub fn line_column(
+        &self,
+        files: &crate::Files,
+        base: u32,
+        tab_width: u32,
+    ) -> Option<(u32, u32)> {
+        let location = files.location(self.file, self.span.start).ok()?;
+        let line = files.line_span(self.file, location.line).ok()?;
+        let source_line = files.source_slice(self.file, line).ok()?;
+        // everything on the same line before the start of the location
+        let before = &source_line[..(self.span.start - line.start().0) as usize];
+        Some((
+            location.line.0 + base,
+            display_column(before, tab_width) + base,
+        ))
+    }
+}
+
+/// The 0-indexed column after displaying `line`, where tabs advance to the next multiple of `tab_width`.
+fn display_column(line: &str, tab_width: u32) -> u32 {
+    let tab_width = tab_width.max(1);
+    line.chars().fold(0, |column, c| {
+        if c == '\t' {
+            column + tab_width - column % tab_width
+        } else {
+            column + 1
+        }
+    })
 }
 
 impl<T: PartialEq> PartialEq for Locatable<T> {
@@ -298,6 +416,50 @@ impl<T> Locatable<T> {
 
 impl Token {
     pub const EQUAL: Token = Token::Assignment(AssignmentToken::Equal);
+
+    /// Return the category of this token, discarding any identifiers or literals.
+    pub fn kind(&self) -> TokenKind {
+        use TokenKind as K;
+        match self {
+            Token::PlusPlus => K::PlusPlus,
+            Token::MinusMinus => K::MinusMinus,
+            Token::Assignment(a) => K::Assignment(*a),
+            Token::Comparison(c) => K::Comparison(*c),
+            Token::Plus => K::Plus,
+            Token::Minus => K::Minus,
+            Token::Star => K::Star,
+            Token::Divide => K::Divide,
+            Token::Mod => K::Mod,
+            Token::Xor => K::Xor,
+            Token::Ampersand => K::Ampersand,
+            Token::LogicalAnd => K::LogicalAnd,
+            Token::BitwiseOr => K::BitwiseOr,
+            Token::LogicalOr => K::LogicalOr,
+            Token::BinaryNot => K::BinaryNot,
+            Token::LogicalNot => K::LogicalNot,
+            Token::ShiftRight => K::ShiftRight,
+            Token::ShiftLeft => K::ShiftLeft,
+            Token::LeftBrace => K::LeftBrace,
+            Token::RightBrace => K::RightBrace,
+            Token::LeftBracket => K::LeftBracket,
+            Token::RightBracket => K::RightBracket,
+            Token::LeftParen => K::LeftParen,
+            Token::RightParen => K::RightParen,
+            Token::Semicolon => K::Semicolon,
+            Token::Colon => K::Colon,
+            Token::Comma => K::Comma,
+            Token::Dot => K::Dot,
+            Token::Question => K::Question,
+            Token::Keyword(k) => K::Keyword(*k),
+            Token::Literal(_) => K::Literal,
+            Token::Id(_) => K::Id,
+            Token::Whitespace(_) => K::Whitespace,
+            Token::Ellipsis => K::Ellipsis,
+            Token::StructDeref => K::StructDeref,
+            Token::Hash => K::Hash,
+            Token::Pragma(_) => K::Pragma,
+        }
+    }
 }
 
 impl AssignmentToken {
@@ -345,6 +507,11 @@ impl std::fmt::Display for Keyword {
             Keyword::ThreadLocal => write!(f, "_Thread_local"),
             Keyword::StaticAssert => write!(f, "_Static_assert"),
             Keyword::VaList => write!(f, "va_list"),
+            Keyword::AutoType => write!(f, "__auto_type"),
+            Keyword::GnuAlignof => write!(f, "__alignof__"),
+            Keyword::Asm => write!(f, "__asm__"),
+            Keyword::TypesCompatible => write!(f, "__builtin_types_compatible_p"),
+            Keyword::ChooseExpr => write!(f, "__builtin_choose_expr"),
             _ => write!(f, "{}", &format!("{:?}", self).to_lowercase()),
         }
     }
@@ -393,6 +560,10 @@ impl std::fmt::Display for Token {
             Ellipsis => write!(f, "..."),
             StructDeref => write!(f, "->"),
             Hash => write!(f, "#"),
+            Pragma(pragma) => {
+                let escaped = pragma.replace('\\', "\\\\").replace('"', "\\\"");
+                write!(f, "_Pragma(\"{}\")", escaped)
+            }
         }
     }
 }
@@ -403,7 +574,7 @@ impl std::fmt::Display for LiteralToken {
         match self {
             Int(i) => write!(f, "{}", i),
             UnsignedInt(u) => write!(f, "{}", u),
-            Float(n) => write!(f, "{}", n),
+            Float(n) | Imaginary(n) => write!(f, "{}", n),
             Str(s) => write!(f, "{}", s.join(" ")),
             Char(s) => write!(f, "{}", s),
         }
@@ -499,6 +670,77 @@ pub(crate) mod test {
         PreProcessorBuilder::new(s).build()
     }
 
+    #[test]
+    fn line_column() {
+        use crate::data::Location;
+
+        let mut files = crate::Files::default();
+        let file = files.add("<test>", String::from("int x;\nint y;\n").into());
+        // `y`
+        let location = Location {
+            span: (11..12).into(),
+            file,
+        };
+        assert_eq!(location.line_column(&files, 0, 8), Some((1, 4)));
+        assert_eq!(location.line_column(&files, 1, 8), Some((2, 5)));
+
+        // the same thing for a real declaration
+        let program = check_semantics("int x;\nint y;\n", Opt::default());
+        let y = program.result.unwrap()[1].location;
+        let (line, column) = y.line_column(&program.files, 0, 8).unwrap();
+        assert_eq!(line, 1);
+        assert_eq!(y.line_column(&program.files, 1, 8), Some((2, column + 1)));
+    }
+    #[test]
+    fn line_column_tab_width() {
+        use crate::data::Location;
+
+        let mut files = crate::Files::default();
+        let file = files.add("<test>", String::from("\tint x;\nab\tc;\n").into());
+        // `x`
+        let x = Location {
+            span: (5..6).into(),
+            file,
+        };
+        assert_eq!(x.line_column(&files, 0, 8), Some((0, 12)));
+        assert_eq!(x.line_column(&files, 1, 4), Some((1, 9)));
+        // `c`: tabs go to the next tab stop, not a fixed number of columns
+        let c = Location {
+            span: (11..12).into(),
+            file,
+        };
+        assert_eq!(c.line_column(&files, 0, 4), Some((1, 4)));
+        assert_eq!(c.line_column(&files, 0, 1), Some((1, 3)));
+    }
+    #[test]
+    fn locatable_combinators() {
+        use crate::data::{Locatable, Location};
+
+        let location = Location {
+            span: (1..4).into(),
+            ..Location::default()
+        };
+        let num = Locatable::new(3, location);
+        let doubled = num.map(|n| n * 2);
+        assert_eq!(doubled.data, 6);
+        assert_eq!(doubled.location, location);
+
+        let ok = num.and_then(|n| -> Result<_, ()> { Ok(n + 1) }).unwrap();
+        assert_eq!(ok.data, 4);
+        assert_eq!(ok.location, location);
+        let err = num.and_then(|_| Err::<(), _>("bad")).unwrap_err();
+        assert_eq!(err.data, "bad");
+        assert_eq!(err.location, location);
+
+        let result: Locatable<Result<i32, &str>> = Locatable::new(Err("bad"), location);
+        let err = result.map_err(str::len).unwrap_err();
+        assert_eq!(err.data, 3);
+        assert_eq!(err.location, location);
+        let result: Locatable<Result<i32, &str>> = Locatable::new(Ok(1), location);
+        let ok = result.map_err(str::len).unwrap();
+        assert_eq!(ok.data, 1);
+        assert_eq!(ok.location, location);
+    }
     #[test]
     fn assignment_display() {
         let tokens = [
@@ -88,9 +88,85 @@ impl TryFrom<u32> for Radix {
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use crate::analyze::test::analyze;
     use crate::Parser;
+    use std::fmt::Debug;
+
+    /// Describe the first structural difference between two trees, or `None` if they're equal.
+    ///
+    /// This is meant for test failures, where `assert_eq!` on a large tree is hard to read.
+    /// Like `PartialEq` for `Locatable`, this ignores locations.
+    pub(crate) fn diff<T: Debug>(expected: &T, actual: &T) -> Option<String> {
+        let expected = debug_lines(expected);
+        let actual = debug_lines(actual);
+        let len = expected.len().max(actual.len());
+        let i = (0..len).find(|&i| expected.get(i) != actual.get(i))?;
+        let line = |lines: &[String]| {
+            lines
+                .get(i)
+                .map_or("<nothing>".to_string(), |l| l.trim().to_string())
+        };
+        Some(format!(
+            "trees differ at `{}`\n  expected: {}\n  actual:   {}",
+            path_to(&expected, i),
+            line(&expected),
+            line(&actual)
+        ))
+    }
+    fn debug_lines<T: Debug>(tree: &T) -> Vec<String> {
+        let debug = format!("{:#?}", tree);
+        let mut lines = Vec::new();
+        let mut skip_until = None;
+        for line in debug.lines() {
+            let indent = line.len() - line.trim_start().len();
+            match skip_until {
+                Some(end) if indent == end && line.trim_start().starts_with('}') => {
+                    skip_until = None;
+                }
+                Some(_) => {}
+                None if line.trim_start().starts_with("location: Location {") => {
+                    skip_until = Some(indent);
+                }
+                None => lines.push(line.to_string()),
+            }
+        }
+        lines
+    }
+    /// The lines enclosing `lines[i]`, from the outside in
+    fn path_to(lines: &[String], i: usize) -> String {
+        let indent = |l: &str| l.len() - l.trim_start().len();
+        let mut current = lines.get(i).map_or(usize::MAX, |l| indent(l));
+        let mut path = Vec::new();
+        for line in lines[..i.min(lines.len())].iter().rev() {
+            if indent(line) < current {
+                current = indent(line);
+                let trimmed = line.trim().trim_end_matches(&['{', '(', '['][..]);
+                path.push(trimmed.trim_end().to_string());
+            }
+        }
+        path.reverse();
+        path.join(" > ")
+    }
+
+    #[test]
+    fn test_diff() {
+        let expr = |s| crate::parse::test::parser(s).expr().unwrap();
+
+        let left = expr("a + b * c");
+        // parentheses only change the location, which is ignored
+        assert_eq!(diff(&left, &expr("a + (b * c)")), None);
+
+        let message = diff(&left, &expr("a + b * d")).unwrap();
+        assert!(message.contains("data: Add > "), "{}", message);
+        assert!(message.contains("data: Mul > "), "{}", message);
+        assert!(message.contains("expected: c"), "{}", message);
+        assert!(message.contains("actual:   d"), "{}", message);
+
+        let message = diff(&left, &expr("a + b / c")).unwrap();
+        assert!(message.contains("expected: data: Mul("), "{}", message);
+        assert!(message.contains("actual:   data: Div("), "{}", message);
+    }
 
     #[test]
     fn type_display() {
@@ -181,6 +181,22 @@ pub struct FunctionType {
 }
 
 impl Type {
+    /// Construct a pointer to this type.
+    pub fn pointer_to(self, quals: super::hir::Qualifiers) -> Type {
+        Type::Pointer(Box::new(self), quals)
+    }
+    /// Construct an array with this type as its element type.
+    pub fn array_of(self, size: ArrayType) -> Type {
+        Type::Array(Box::new(self), size)
+    }
+    /// Construct a function with this type as its return type.
+    pub fn function(self, params: Vec<Symbol>, varargs: bool) -> Type {
+        Type::Function(FunctionType {
+            return_type: Box::new(self),
+            params,
+            varargs,
+        })
+    }
     /// https://stackoverflow.com/questions/14821936/what-is-a-scalar-object-in-c#14822074
     #[inline]
     pub fn is_scalar(&self) -> bool {
@@ -432,7 +448,7 @@ pub(super) fn print_type(
 pub(crate) mod tests {
     use proptest::prelude::*;
 
-    use super::{ArrayType, InternedStr, Type};
+    use super::{ArrayType, FunctionType, InternedStr, Type};
     use crate::data::hir::Qualifiers;
 
     pub(crate) fn arb_type() -> impl Strategy<Value = Type> {
@@ -478,4 +494,45 @@ pub(crate) mod tests {
         assert_no_change("int *(*jynelson)(int (*)(int));");
         assert_no_change("int f(int, ...);");
     }
+
+    #[test]
+    fn test_builders() {
+        let quals = Qualifiers {
+            c_const: true,
+            ..Qualifiers::default()
+        };
+        assert_eq!(
+            Type::Int(true).pointer_to(quals),
+            Type::Pointer(Box::new(Type::Int(true)), quals)
+        );
+        assert_eq!(
+            Type::Char(false).array_of(ArrayType::Fixed(3)),
+            Type::Array(Box::new(Type::Char(false)), ArrayType::Fixed(3))
+        );
+        assert_eq!(
+            Type::Void.function(vec![], true),
+            Type::Function(FunctionType {
+                return_type: Box::new(Type::Void),
+                params: vec![],
+                varargs: true,
+            })
+        );
+        // int *(*)[2]
+        assert_eq!(
+            Type::Int(true)
+                .pointer_to(Qualifiers::default())
+                .array_of(ArrayType::Fixed(2))
+                .pointer_to(Qualifiers::default()),
+            Type::Pointer(
+                Box::new(Type::Array(
+                    Box::new(Type::Pointer(
+                        Box::new(Type::Int(true)),
+                        Qualifiers::default()
+                    )),
+                    ArrayType::Fixed(2)
+                )),
+                Qualifiers::default()
+            )
+        );
+    }
 }
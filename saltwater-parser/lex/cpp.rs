@@ -63,6 +63,14 @@ pub struct PreProcessorBuilder<'a> {
     search_path: Vec<Cow<'a, Path>>,
     /// The user-defined macros that should be defined at startup
     definitions: Definitions,
+    /// Whether to keep comments in whitespace tokens
+    preserve_comments: bool,
+    /// Whether the input has already been preprocessed
+    preprocessed: bool,
+    /// Whether to recognize the keywords added in C23
+    c23: bool,
+    /// Whether to recognize GNU keywords that are ordinary identifiers in ISO C
+    gnu_extensions: bool,
 }
 
 impl<'a> PreProcessorBuilder<'a> {
@@ -73,6 +81,10 @@ impl<'a> PreProcessorBuilder<'a> {
             buf: buf.into(),
             search_path: Vec::new(),
             definitions: Definitions::new(),
+            preserve_comments: false,
+            preprocessed: false,
+            c23: false,
+            gnu_extensions: false,
         }
     }
     pub fn filename<P: Into<PathBuf>>(mut self, name: P) -> Self {
@@ -91,14 +103,49 @@ impl<'a> PreProcessorBuilder<'a> {
         self.definitions.insert(name, def.into());
         self
     }
+    /// Keep the text of comments in `Token::Whitespace` instead of discarding it.
+    ///
+    /// The parser uses this to attach comments to the declaration that follows them.
+    pub fn preserve_comments(mut self, yes: bool) -> Self {
+        self.preserve_comments = yes;
+        self
+    }
+    /// Treat the input as already preprocessed, e.g. the output of `cpp` or `swcc -E`.
+    ///
+    /// Macros will not be replaced and any preprocessing directive is an error.
+    /// By default, the input is assumed to be raw C source and directives are handled as usual.
+    pub fn preprocessed(mut self, yes: bool) -> Self {
+        self.preprocessed = yes;
+        self
+    }
+    /// Recognize the lowercase keywords added in C23, like `alignas` and `alignof`.
+    ///
+    /// By default, these are ordinary identifiers, as they were in C11.
+    pub fn c23(mut self, yes: bool) -> Self {
+        self.c23 = yes;
+        self
+    }
+    /// Recognize the GNU keywords in `GNU_KEYWORDS`, like `asm`.
+    ///
+    /// By default, these are ordinary identifiers, as ISO C requires.
+    /// The reserved spellings, like `__asm__`, are always keywords.
+    pub fn gnu_extensions(mut self, yes: bool) -> Self {
+        self.gnu_extensions = yes;
+        self
+    }
     pub fn build(self) -> PreProcessor<'a> {
-        PreProcessor::new(
+        let mut cpp = PreProcessor::new(
             self.buf,
             self.filename,
             self.debug,
             self.search_path,
             self.definitions,
-        )
+        );
+        cpp.file_processor.preserve_comments(self.preserve_comments);
+        cpp.preprocessed = self.preprocessed;
+        cpp.c23 = self.c23;
+        cpp.gnu_extensions = self.gnu_extensions;
+        cpp
     }
 }
 
@@ -140,6 +187,13 @@ pub struct PreProcessor<'a> {
     definitions: Definitions,
     /// Handles reading from files
     file_processor: FileProcessor,
+    /// If set, the input has already been preprocessed:
+    /// directives are errors and macros are not replaced.
+    preprocessed: bool,
+    /// If set, the C23 spellings in `C23_KEYWORDS` are keywords instead of identifiers.
+    c23: bool,
+    /// If set, the GNU spellings in `GNU_KEYWORDS` are keywords instead of identifiers.
+    gnu_extensions: bool,
 }
 
 enum PendingToken {
@@ -250,6 +304,17 @@ impl Iterator for PreProcessor<'_> {
                 }
             };
             if let Some(token) = replacement {
+                // _Pragma("once")
+                if let Ok(Locatable {
+                    data: Token::Id(id),
+                    location,
+                }) = &token
+                {
+                    if get_str!(id) == "_Pragma" {
+                        let location = *location;
+                        return Some(self.pragma_operator(location));
+                    }
+                }
                 return Some(token);
             }
             // This token was an empty define, so continue looking for tokens
@@ -278,6 +343,9 @@ impl<'a> PreProcessor<'a> {
             // avoids infinite loops on cyclic defines (#298)
             match token {
                 PendingToken::Replaced(t) => Some(Ok(Locatable::new(t, location))),
+                PendingToken::NeedsReplacement(t) if self.preprocessed => {
+                    Some(Ok(Locatable::new(t, location)))
+                }
                 PendingToken::NeedsReplacement(token) => {
                     self.update_builtin_definitions();
                     let mut replacement_list =
@@ -300,8 +368,15 @@ impl<'a> PreProcessor<'a> {
         })) = &mut token
         {
             if let Token::Id(name) = &data {
-                if let Some(keyword) = KEYWORDS.get(get_str!(name)) {
-                    *data = Token::Keyword(*keyword);
+                let mut keyword = KEYWORDS.get(get_str!(name)).copied();
+                if keyword.is_none() && self.c23 {
+                    keyword = C23_KEYWORDS.get(get_str!(name)).copied();
+                }
+                if keyword.is_none() && self.gnu_extensions {
+                    keyword = GNU_KEYWORDS.get(get_str!(name)).copied();
+                }
+                if let Some(keyword) = keyword {
+                    *data = Token::Keyword(keyword);
                 }
             }
         }
@@ -360,6 +435,9 @@ impl<'a> PreProcessor<'a> {
             search_path,
             definitions,
             file_processor,
+            preprocessed: false,
+            c23: false,
+            gnu_extensions: false,
         };
         new_cpp.update_builtin_definitions(); // So they are defined from the start
         new_cpp
@@ -430,31 +508,38 @@ impl<'a> PreProcessor<'a> {
             }) => true,
             _ => false,
         };
-        Some(if is_hash && !self.file_processor.seen_line_token() {
-            let line = self.file_processor.line();
-            match self.file_processor.next_non_whitespace()? {
-                Ok(Locatable {
-                    data: Token::Id(id),
-                    location,
-                }) if self.file_processor.line() == line => {
-                    if let Ok(directive) = DirectiveKind::try_from(get_str!(id)) {
-                        Ok(Locatable::new(CppToken::Directive(directive), location))
-                    } else {
-                        Err(Locatable::new(CppError::InvalidDirective.into(), location))
+        Some(
+            if is_hash && !self.file_processor.seen_line_token() && self.preprocessed {
+                // skip the rest of the directive so we only report one error
+                let location = next_token.unwrap().location;
+                drop(self.tokens_until_newline(false));
+                Err(location.error(CppError::DirectiveInPreprocessedInput))
+            } else if is_hash && !self.file_processor.seen_line_token() {
+                let line = self.file_processor.line();
+                match self.file_processor.next_non_whitespace()? {
+                    Ok(Locatable {
+                        data: Token::Id(id),
+                        location,
+                    }) if self.file_processor.line() == line => {
+                        if let Ok(directive) = DirectiveKind::try_from(get_str!(id)) {
+                            Ok(Locatable::new(CppToken::Directive(directive), location))
+                        } else {
+                            Err(Locatable::new(CppError::InvalidDirective.into(), location))
+                        }
                     }
-                }
-                Ok(other) => {
-                    if self.file_processor.line() == line {
-                        Err(other.map(|tok| CppError::UnexpectedToken("directive", tok).into()))
-                    } else {
-                        Ok(other.into())
+                    Ok(other) => {
+                        if self.file_processor.line() == line {
+                            Err(other.map(|tok| CppError::UnexpectedToken("directive", tok).into()))
+                        } else {
+                            Ok(other.into())
+                        }
                     }
+                    other => other.map(Locatable::from),
                 }
-                other => other.map(Locatable::from),
-            }
-        } else {
-            next_token.map(Locatable::from)
-        })
+            } else {
+                next_token.map(Locatable::from)
+            },
+        )
     }
     // this function does _not_ perform macro substitution
     fn expect_id(&mut self) -> CppResult<InternedStr> {
@@ -474,6 +559,34 @@ impl<'a> PreProcessor<'a> {
             }),
         }
     }
+    /// 6.10.9 Pragma operator: `_Pragma ( string-literal )`
+    ///
+    /// This assumes `_Pragma` has already been consumed.
+    /// Unlike `#pragma`, the pragma is passed on to the parser as a `Token::Pragma`.
+    fn pragma_operator(&mut self, start: Location) -> CppResult<Token> {
+        let expect =
+            |this: &mut Self, expected: &'static str, matches: fn(&Token) -> bool| match this
+                .next_non_whitespace()
+            {
+                Some(Ok(token)) if matches(&token.data) => Ok(token),
+                Some(Ok(token)) => {
+                    Err(token.map(|tok| CppError::UnexpectedToken(expected, tok).into()))
+                }
+                Some(Err(err)) => Err(err),
+                None => Err(start.error(CppError::EndOfFile(expected))),
+            };
+        expect(self, "'(' after _Pragma", |t| *t == Token::LeftParen)?;
+        let literal = expect(self, "string literal in _Pragma", |t| match t {
+            Token::Literal(LiteralToken::Str(_)) => true,
+            _ => false,
+        })?;
+        let end = expect(self, "')' after _Pragma", |t| *t == Token::RightParen)?.location;
+        let pragma = match literal.data {
+            Token::Literal(LiteralToken::Str(strs)) => destringize(strs[0].as_str()),
+            _ => unreachable!("checked by `expect`"),
+        };
+        Ok(start.merge(end).with(Token::Pragma(pragma)))
+    }
     // Handle a directive. This assumes we have consumed the directive (e.g. `#if`),
     // but not the rest of the tokens on the current line.
     fn directive(&mut self, kind: DirectiveKind, start: u32) -> Result<(), CompileError> {
@@ -1192,6 +1305,25 @@ const PRECOMPILED_HEADERS: [(&str, &str); 2] = built_in_headers! {
     "stddef.h",
 };
 
+/// 6.10.9 Pragma operator: the string literal is destringized
+/// by deleting any encoding prefix and the surrounding quotes,
+/// and replacing `\"` with `"` and `\\` with `\`.
+fn destringize(literal: &str) -> String {
+    let start = literal
+        .find('"')
+        .expect("string literals start with a quote");
+    let mut chars = literal[start + 1..literal.len() - 1].chars().peekable();
+    let mut pragma = String::new();
+    while let Some(c) = chars.next() {
+        if c == '\\' && (chars.peek() == Some(&'"') || chars.peek() == Some(&'\\')) {
+            pragma.push(chars.next().unwrap());
+        } else {
+            pragma.push(c);
+        }
+    }
+    pragma
+}
+
 fn get_builtin_header(expected: impl AsRef<str>) -> Option<&'static str> {
     PRECOMPILED_HEADERS
         .iter()
@@ -1309,6 +1441,38 @@ lazy_static! {
         "_Alignas" => Keyword::Alignas,
         "_Generic" => Keyword::Generic,
         "_Static_assert" => Keyword::StaticAssert,
+        "__builtin_types_compatible_p" => Keyword::TypesCompatible,
+        "__builtin_choose_expr" => Keyword::ChooseExpr,
+
+        // GNU alternate spellings, used heavily by system headers
+        "__const" => Keyword::Const,
+        "__const__" => Keyword::Const,
+        "__volatile" => Keyword::Volatile,
+        "__volatile__" => Keyword::Volatile,
+        "__restrict" => Keyword::Restrict,
+        "__restrict__" => Keyword::Restrict,
+        "__signed" => Keyword::Signed,
+        "__signed__" => Keyword::Signed,
+        "__inline" => Keyword::Inline,
+        "__inline__" => Keyword::Inline,
+        "__alignof" => Keyword::GnuAlignof,
+        "__alignof__" => Keyword::GnuAlignof,
+        "__asm" => Keyword::Asm,
+        "__asm__" => Keyword::Asm,
+        "__auto_type" => Keyword::AutoType,
+    };
+
+    /// Keywords which were only reserved identifiers before C23
+    static ref C23_KEYWORDS: HashMap<&'static str, Keyword> = map!{
+        "alignas" => Keyword::Alignas,
+        "alignof" => Keyword::Alignof,
+    };
+
+    /// GNU keywords which are only recognized in GNU mode:
+    /// ISO C either allows them as identifiers (`asm`) or gives them no meaning (`__thread`)
+    static ref GNU_KEYWORDS: HashMap<&'static str, Keyword> = map!{
+        "asm" => Keyword::Asm,
+        "__thread" => Keyword::ThreadLocal,
     };
 }
 
@@ -1371,6 +1535,83 @@ mod tests {
         }
     }
     #[test]
+    fn preprocessed_input() {
+        let tokens = |preprocessed| {
+            PreProcessorBuilder::new("#define X 1\nint x = X;\n")
+                .preprocessed(preprocessed)
+                .build()
+                .filter(PreProcessor::is_not_whitespace)
+                .collect::<Vec<_>>()
+        };
+        // by default, directives are handled
+        let raw = tokens(false);
+        assert!(raw.iter().all(Result::is_ok));
+        assert_eq!(
+            raw[3].as_ref().unwrap().data,
+            Token::Literal(LiteralToken::Int(arcstr::literal_substr!("1")))
+        );
+
+        let preprocessed = tokens(true);
+        assert_eq!(
+            preprocessed[0].as_ref().unwrap_err().data,
+            CppError::DirectiveInPreprocessedInput.into()
+        );
+        // only one error for the whole line, and `X` isn't replaced
+        assert!(preprocessed[1..].iter().all(Result::is_ok));
+        assert_eq!(
+            preprocessed[4].as_ref().unwrap().data,
+            Token::Id("X".into())
+        );
+    }
+    #[test]
+    fn gnu_keyword_aliases() {
+        for (alias, keyword) in &[
+            ("__const", Keyword::Const),
+            ("__const__", Keyword::Const),
+            ("__volatile__", Keyword::Volatile),
+            ("__restrict__", Keyword::Restrict),
+            ("__signed__", Keyword::Signed),
+            ("__inline__", Keyword::Inline),
+        ] {
+            assert_keyword(cpp(alias).next(), *keyword);
+        }
+    }
+    #[test]
+    fn c23_keywords() {
+        let c23 = |src: &str| PreProcessorBuilder::new(src).c23(true).build().next();
+        assert_keyword(c23("alignas"), Keyword::Alignas);
+        assert_keyword(c23("alignof"), Keyword::Alignof);
+        // the old spellings still work
+        assert_keyword(c23("_Alignas"), Keyword::Alignas);
+        // before C23, these are ordinary identifiers
+        for id in &["alignas", "alignof"] {
+            assert_eq!(
+                cpp(id).next().unwrap().unwrap().data,
+                Token::Id((*id).into())
+            );
+        }
+    }
+    #[test]
+    fn gnu_keywords() {
+        let gnu = |src: &str| {
+            PreProcessorBuilder::new(src)
+                .gnu_extensions(true)
+                .build()
+                .next()
+        };
+        assert_keyword(gnu("asm"), Keyword::Asm);
+        assert_keyword(gnu("__thread"), Keyword::ThreadLocal);
+        // the reserved spellings are always keywords
+        assert_keyword(cpp("__asm__").next(), Keyword::Asm);
+        assert_keyword(cpp("__asm").next(), Keyword::Asm);
+        for id in &["asm", "__thread"] {
+            assert_eq!(
+                cpp(id).next().unwrap().unwrap().data,
+                Token::Id((*id).into())
+            );
+        }
+    }
+    #[test]
     fn if_directive() {
         assert_same(
             "
@@ -1630,6 +1871,35 @@ d
         assert!(cpp(src).next_non_whitespace().is_none());
     }
     #[test]
+    fn pragma_operator() {
+        let mut once = cpp("_Pragma(\"once\") x");
+        assert_eq!(
+            once.next_non_whitespace().unwrap().unwrap().data,
+            Token::Pragma("once".into())
+        );
+        assert_eq!(
+            once.next_non_whitespace().unwrap().unwrap().data,
+            Token::Id("x".into())
+        );
+        assert!(once.warnings().is_empty());
+        let pragma = |src| cpp(src).next_non_whitespace().unwrap().unwrap().data;
+        assert_eq!(
+            pragma(r#"_Pragma("message(\"a\\\\b\")")"#),
+            Token::Pragma(r#"message("a\\b")"#.into())
+        );
+        // operators can come from macros, unlike directives
+        assert_same(
+            "#define ONCE _Pragma(\"once\")\nONCE x",
+            "_Pragma(\"once\") x",
+        );
+        assert_err!(
+            "_Pragma(once)",
+            CppError::UnexpectedToken(..),
+            "string literal"
+        );
+        assert_err!("_Pragma(\"once\"", CppError::EndOfFile(_), "')'");
+    }
+    #[test]
     fn line() {
         let src = "#line 1";
         let mut cpp = cpp(src);
@@ -86,6 +86,11 @@ impl FileProcessor {
     pub(super) fn lexer_mut(&mut self) -> &mut Lexer {
         self.includes.last_mut().unwrap_or(&mut self.first_lexer)
     }
+    /// Keep comments in the main file as part of whitespace tokens.
+    /// Comments in `#include`d files are always discarded.
+    pub(super) fn preserve_comments(&mut self, yes: bool) {
+        self.first_lexer.preserve_comments = yes;
+    }
     pub(super) fn add_file(&mut self, filename: PathBuf, source: Source) {
         let code = ArcStr::clone(&source.code);
         let id = self.files.add(filename, source);
@@ -51,6 +51,9 @@ pub struct Lexer {
     /// Whether or not to display each token as it is processed
     debug: bool,
     given_newline_error: bool,
+    /// Whether to keep the text of comments in `Token::Whitespace`
+    /// instead of replacing them with a space.
+    pub(crate) preserve_comments: bool,
 }
 
 struct PseudoLexer<T: Iterator<Item = char>> {
@@ -88,6 +91,7 @@ impl Lexer {
             current: None,
             lookahead: None,
             error_handler: ErrorHandler::new(),
+            preserve_comments: false,
         }
     }
 
@@ -108,6 +112,36 @@ impl Lexer {
         })
     }
 
+    /// Like `consume_whitespace`, but keeps the text of comments
+    /// instead of replacing them with whitespace.
+    fn consume_whitespace_and_comments(&mut self) -> String {
+        let mut whitespace = String::new();
+        loop {
+            while self.peek().map_or(false, |c| c.is_ascii_whitespace()) {
+                if let Some(c) = self.next_char() {
+                    whitespace.push(c);
+                }
+            }
+            let start = self.location.offset;
+            if self.peek() != Some('/') {
+                break;
+            }
+            match self.peek_next() {
+                Some('/') => self.consume_line_comment(),
+                Some('*') => {
+                    self.next_char();
+                    self.next_char();
+                    if let Err(err) = self.consume_multi_comment() {
+                        self.err(err);
+                    }
+                }
+                _ => break,
+            }
+            whitespace.push_str(&self.chars[start as usize..self.location.offset as usize]);
+        }
+        whitespace
+    }
+
     // TODO: this _really_ needs to be refactored
     fn chars(&self) -> std::str::Chars<'_> {
         use std::mem;
@@ -141,7 +175,7 @@ impl Lexer {
             "main loop should only pass [0-9] as start to parse_num"
         );
         let span_start = self.get_location().offset - 1; // -1 for `start`
-        let float_literal = |f| Token::Literal(LiteralToken::Float(f));
+        let float_literal = |f| Token::Literal(float_token(f));
         let mut buf = String::new();
         buf.push(start as char);
         // check for radix other than 10 - but if we see '.', use 10
@@ -178,23 +212,29 @@ impl Lexer {
             self.consume_float_suffix();
             return Ok(self.slice(span_start)).map(float_literal);
         }
-        let literal = if self.match_next('u') || self.match_next('U') {
+        let literal = if self.consume_int_suffix() {
             LiteralToken::UnsignedInt(self.slice(span_start))
         } else {
             LiteralToken::Int(self.slice(span_start))
         };
-        // get rid of 'l' and 'll' suffixes, we don't handle them
-        if self.match_next('l') {
-            self.match_next('l');
-        } else if self.match_next('L') {
-            self.match_next('L');
-        }
         if radix == Radix::Binary {
             let span = self.span(span_start);
             self.warn_loc("binary number literals are an extension", span);
         }
         Ok(Token::Literal(literal))
     }
+    /// Consume an integer suffix (`u`, `l`, `ll`, in either order), returning whether it was unsigned.
+    ///
+    /// `long` and `long long` are the same size, so the length suffix doesn't change the type.
+    fn consume_int_suffix(&mut self) -> bool {
+        let unsigned = self.match_next('u') || self.match_next('U');
+        if self.match_next('l') {
+            self.match_next('l');
+        } else if self.match_next('L') {
+            self.match_next('L');
+        }
+        unsigned || self.match_next('u') || self.match_next('U')
+    }
     // at this point we've already seen a '.', if we see one again it's an error
     fn parse_float(&mut self, radix: Radix, span_start: u32) -> Result<Substr, LexError> {
         // parse fraction: second {digits} in regex
@@ -211,10 +251,18 @@ impl Lexer {
         Ok(self.slice(span_start))
     }
     fn consume_float_suffix(&mut self) {
+        // GNU extension: `1.0i` is imaginary; the `i` can go before or after the other suffix
+        let imaginary = self.consume_imaginary_suffix();
         // Ignored for compatibility reasons
         if !(self.match_next('f') || self.match_next('F') || self.match_next('l')) {
             self.match_next('L');
         }
+        if !imaginary {
+            self.consume_imaginary_suffix();
+        }
+    }
+    fn consume_imaginary_suffix(&mut self) -> bool {
+        self.match_next('i') || self.match_next('I') || self.match_next('j') || self.match_next('J')
     }
     // should only be called at the end of a number. mostly error handling
     fn parse_exponent(&mut self, hex: bool) -> Result<(), LexError> {
@@ -254,6 +302,16 @@ impl Lexer {
         };
         let mut saw_digit = false;
         while let Some(c) = self.peek() {
+            // C2x digit separators: 1'000'000
+            // the first digit of a decimal or octal literal has already been consumed
+            let after_digit = saw_digit || radix == Radix::Decimal || radix == Radix::Octal;
+            if c == '\'' && after_digit {
+                self.next_char();
+                match self.peek().map(parse_digit) {
+                    Some(Ok(Some(_))) => continue,
+                    _ => return Err(LexError::MisplacedDigitSeparator),
+                }
+            }
             match parse_digit(c as char)? {
                 Some(_) => {
                     self.next_char();
@@ -345,7 +403,11 @@ impl Iterator for Lexer {
 
         {
             let span_start = self.location.offset;
-            let data = self.consume_whitespace();
+            let data = if self.preserve_comments {
+                self.consume_whitespace_and_comments()
+            } else {
+                self.consume_whitespace()
+            };
             check_no_newline(self);
             if !data.is_empty() {
                 return Some(Ok(Locatable {
@@ -493,7 +555,7 @@ impl Iterator for Lexer {
                 '.' => match self.peek() {
                     Some(c) if c.is_ascii_digit() => {
                         match self.parse_float(Radix::Decimal, span_start) {
-                            Ok(f) => LiteralToken::Float(f).into(),
+                            Ok(f) => float_token(f).into(),
                             Err(err) => {
                                 return Err(Locatable {
                                     data: err,
@@ -987,7 +1049,7 @@ fn parse_int_raw(buf: &str) -> Result<u64, SyntaxError> {
         (Radix::Decimal, buf)
     };
     let mut acc: u64 = 0;
-    for c in buf.chars() {
+    for c in buf.chars().filter(|&c| c != '\'') {
         let digit = c.to_digit(radix.as_u8().into());
         let digit = match digit {
             Some(digit) => digit,
@@ -1002,6 +1064,40 @@ fn parse_int_raw(buf: &str) -> Result<u64, SyntaxError> {
     Ok(acc)
 }
 
+/// `1.0` is a float, but `1.0i` is an imaginary constant (a GNU extension).
+fn float_token(spelling: Substr) -> LiteralToken {
+    let mut suffix = spelling
+        .chars()
+        .rev()
+        .take_while(|&c| "fFlLiIjJ".contains(c));
+    if suffix.any(|c| "iIjJ".contains(c)) {
+        LiteralToken::Imaginary(spelling)
+    } else {
+        LiteralToken::Float(spelling)
+    }
+}
+
+/// Parse the spelling of a floating constant, ignoring any suffix.
+pub(crate) fn parse_float_raw(buf: &str) -> Result<f64, SyntaxError> {
+    let hex = buf.starts_with("0x");
+    let buf = buf.trim_end_matches(|c| "fFlLiIjJ".contains(c));
+    let float: f64 = if hex {
+        let float_literal: hexponent::FloatLiteral = buf.parse()?;
+        float_literal.into()
+    } else {
+        buf.parse()?
+    };
+    let should_be_zero = buf.chars().all(|c| match c {
+        '.' | '+' | '-' | 'e' | 'p' | '0' => true,
+        _ => false,
+    });
+    if float == 0.0 && !should_be_zero {
+        Err(SyntaxError::FloatUnderflow)
+    } else {
+        Ok(float)
+    }
+}
+
 impl LiteralToken {
     pub fn parse(self) -> Result<LiteralValue, SyntaxError> {
         match self {
@@ -1015,26 +1111,11 @@ impl LiteralToken {
             LiteralToken::UnsignedInt(rcstr) => {
                 Ok(LiteralValue::UnsignedInt(parse_int_raw(rcstr.as_str())?))
             }
-            LiteralToken::Float(rcstr) => {
-                let buf = rcstr.as_str();
-                let hex = buf.starts_with("0x");
-                let buf = buf.trim_end_matches(|c| "fFlL".contains(c));
-                let float: f64 = if hex {
-                    let float_literal: hexponent::FloatLiteral = buf.parse()?;
-                    float_literal.into()
-                } else {
-                    buf.parse()?
-                };
-                let should_be_zero = buf.chars().all(|c| match c {
-                    '.' | '+' | '-' | 'e' | 'p' | '0' => true,
-                    _ => false,
-                });
-                if float == 0.0 && !should_be_zero {
-                    Err(SyntaxError::FloatUnderflow)
-                } else {
-                    Ok(LiteralValue::Float(float))
-                }
-            }
+            LiteralToken::Float(rcstr) => parse_float_raw(rcstr.as_str()).map(LiteralValue::Float),
+            // there's no `LiteralValue` for complex numbers, see `parse_float_raw`
+            LiteralToken::Imaginary(_) => Err(SyntaxError::Generic(
+                "imaginary constants are not allowed here".into(),
+            )),
             LiteralToken::Str(strs) => {
                 let num_strs = strs.len();
                 Ok(LiteralValue::Str(
@@ -165,6 +165,41 @@ fn test_int_literals() {
     assert_eq!(lex_all("1a").len(), 2);
 }
 #[test]
+fn test_digit_separators() {
+    assert_int("1'000'000", 1_000_000);
+    assert_int("1'024", 1024);
+    assert_int("0b1'000", 8);
+    assert_int("0x1'F", 31);
+    assert_int("01'7", 15);
+    assert_err("1''0");
+    assert_err("1'");
+    assert_err("1'x");
+}
+#[test]
+fn test_int_suffixes() {
+    assert_int("10LL", 10);
+    assert_int("10l", 10);
+    for (s, expected) in &[
+        ("10u", 10),
+        ("0xFFu", 255),
+        ("10lu", 10),
+        ("10ULL", 10),
+        ("10LLu", 10),
+    ] {
+        assert_eq!(lex_all(s).len(), 1, "{}", s);
+        assert!(
+            match_data(lex(s), |lexed| match lexed.unwrap() {
+                Token::Literal(lit @ LiteralToken::UnsignedInt(_)) =>
+                    lit.clone().parse() == Ok(LiteralValue::UnsignedInt(*expected)),
+                _ => false,
+            }),
+            "{} != {}u",
+            s,
+            expected
+        );
+    }
+}
+#[test]
 fn test_float_literals() {
     assert_float("0.1", 0.1);
     assert_float(".1", 0.1);
@@ -208,6 +243,29 @@ fn test_float_literals() {
     assert_float("2.225073858507201383e-308L", 2.225_073_858_507_201_4e-308);
 }
 
+#[test]
+fn test_imaginary_literals() {
+    for &(s, expected) in &[
+        ("1.0i", 1.0),
+        ("2.5fi", 2.5),
+        ("2.5if", 2.5),
+        (".5j", 0.5),
+        ("1e3I", 1000.0),
+        ("0x1p1i", 2.0),
+    ] {
+        match lex(s) {
+            Some(Ok(Locatable {
+                data: Token::Literal(LiteralToken::Imaginary(spelling)),
+                ..
+            })) => {
+                assert_eq!(spelling.as_str(), s);
+                assert_eq!(super::parse_float_raw(spelling.as_str()), Ok(expected));
+            }
+            other => panic!("expected an imaginary constant for {}, got {:?}", s, other),
+        }
+    }
+}
+
 #[test]
 fn test_num_errors() {
     assert_err("1e");
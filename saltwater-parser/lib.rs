@@ -49,6 +49,18 @@ pub struct Program<T, E = VecDeque<CompileError>> {
     pub files: Files,
 }
 
+impl<T> Program<T> {
+    /// If `yes` is set, turn all warnings into errors.
+    fn warnings_as_errors(mut self, yes: bool) -> Self {
+        if yes && !self.warnings.is_empty() {
+            let mut errs = self.result.err().unwrap_or_default();
+            errs.extend(self.warnings.drain(..).map(|w| w.map(data::Error::Warning)));
+            self.result = Err(errs);
+        }
+        self
+    }
+}
+
 impl<T, E> Program<T, E> {
     fn from_cpp(mut cpp: PreProcessor, result: Result<T, E>) -> Self {
         Program {
@@ -59,7 +71,7 @@ impl<T, E> Program<T, E> {
     }
 }
 
-pub use analyze::{Analyzer, PureAnalyzer};
+pub use analyze::{Analyzer, Grouped, Indexed, PureAnalyzer};
 pub use data::*;
 // https://github.com/rust-lang/rust/issues/64762
 #[allow(unreachable_pub)]
@@ -172,6 +184,9 @@ pub struct Opt {
     /// If set, compile and emit JIT code, and do not emit object files and binaries.
     pub jit: bool,
 
+    /// If set, treat all warnings as errors.
+    pub warnings_as_errors: bool,
+
     /// The maximum number of errors to allow before giving up.
     /// If None, allows an unlimited number of errors.
     pub max_errors: Option<std::num::NonZeroUsize>,
@@ -213,6 +228,93 @@ pub fn preprocess(buf: &str, opt: Opt) -> Program<VecDeque<Locatable<Token>>> {
         warnings: cpp.warnings(),
         files: cpp.into_files(),
     }
+    .warnings_as_errors(opt.warnings_as_errors)
+}
+
+/// Parse the source into an abstract syntax tree, without performing semantic analysis.
+///
+/// All diagnostics are returned in the `Program`, so it is safe to parse several files
+/// at once on different threads. The only state shared between calls is the global
+/// string interner, [`intern::STRINGS`], which is behind a lock.
+pub fn parse(buf: &str, opt: Opt) -> Program<Vec<Locatable<ast::ExternalDeclaration>>> {
+    let warnings_as_errors = opt.warnings_as_errors;
+    let (decls, errs, warnings, files) = parse_declarations(buf, opt);
+    let result = if errs.is_empty() {
+        Ok(decls)
+    } else {
+        Err(errs)
+    };
+    Program {
+        result,
+        warnings,
+        files,
+    }
+    .warnings_as_errors(warnings_as_errors)
+}
+
+/// A whole file after parsing, with every diagnostic found along the way.
+///
+/// Unlike [`parse`], this keeps the declarations that parsed successfully
+/// even if there were errors elsewhere in the file.
+pub struct TranslationUnit {
+    /// The declarations that parsed successfully, in source order
+    pub decls: Vec<Locatable<ast::ExternalDeclaration>>,
+    /// All errors, followed by all warnings (as `Error::Warning`)
+    pub diagnostics: Vec<CompileError>,
+    /// The files that were `#include`d by the preprocessor
+    pub files: Files,
+}
+
+/// Parse a whole file, keeping both the successful declarations and the diagnostics.
+///
+/// `opt.warnings_as_errors` has no effect, since warnings are already returned as diagnostics.
+pub fn parse_translation_unit(buf: &str, opt: Opt) -> TranslationUnit {
+    let (decls, errs, warnings, files) = parse_declarations(buf, opt);
+    let mut diagnostics: Vec<_> = errs.into();
+    diagnostics.extend(warnings.into_iter().map(|w| w.map(data::Error::Warning)));
+    TranslationUnit {
+        decls,
+        diagnostics,
+        files,
+    }
+}
+
+/// Parse declarations until the end of the file or until `opt.max_errors` errors are seen.
+///
+/// Returns the declarations that parsed successfully, the errors, the warnings,
+/// and the files used by the preprocessor.
+fn parse_declarations(
+    buf: &str,
+    opt: Opt,
+) -> (
+    Vec<Locatable<ast::ExternalDeclaration>>,
+    VecDeque<CompileError>,
+    VecDeque<CompileWarning>,
+    Files,
+) {
+    let path = opt.search_path.iter().map(|p| p.into());
+    let mut cpp = PreProcessor::new(buf, opt.filename, opt.debug_lex, path, opt.definitions);
+
+    let mut decls = vec![];
+    let mut errs = VecDeque::new();
+    let mut parser = Parser::new(&mut cpp, opt.debug_ast);
+    for res in &mut parser {
+        match res {
+            Ok(decl) => decls.push(decl),
+            Err(err) => {
+                errs.push_back(err);
+                if let Some(max) = opt.max_errors {
+                    if errs.len() >= max.into() {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    let mut warnings = parser.warnings();
+    warnings.extend(cpp.warnings());
+    (decls, errs, warnings, cpp.into_files())
 }
 
 /// Perform semantic analysis, including type checking and constant folding.
@@ -231,14 +333,17 @@ pub fn check_semantics(buf: &str, opt: Opt) -> Program<Vec<Locatable<hir::Declar
                 errs.push_back(err);
                 if let Some(max) = opt.max_errors {
                     if errs.len() >= max.into() {
-                        return Program::from_cpp(cpp, Err(errs));
+                        let warnings = parser.warnings();
+                        let mut program = Program::from_cpp(cpp, Err(errs));
+                        program.warnings.extend(warnings);
+                        return program.warnings_as_errors(opt.warnings_as_errors);
                     }
                 }
             }
         }
     }
 
-    let mut warnings = parser.inner.warnings();
+    let mut warnings = parser.warnings();
     warnings.extend(cpp.warnings());
     if hir.is_empty() && errs.is_empty() {
         errs.push_back(cpp.eof().error(SemanticError::EmptyProgram));
@@ -249,6 +354,19 @@ pub fn check_semantics(buf: &str, opt: Opt) -> Program<Vec<Locatable<hir::Declar
         warnings,
         files: cpp.into_files(),
     }
+    .warnings_as_errors(opt.warnings_as_errors)
+}
+
+/// Preprocess and parse arbitrary bytes, discarding the result.
+///
+/// This is meant to be used as a fuzz target, so it should never panic.
+/// Invalid UTF-8 is replaced with U+FFFD.
+pub fn fuzz_parse(data: &[u8]) {
+    let opt = Opt {
+        filename: "<fuzz test>".into(),
+        ..Opt::default()
+    };
+    parse(&String::from_utf8_lossy(data), opt);
 }
 
 impl<T: Into<ArcStr>> From<T> for Source {
@@ -294,4 +412,135 @@ mod tests {
         assert!(parse_err.is_empty());
         assert!(err.unwrap().data.is_syntax_err());
     }
+
+    #[test]
+    fn fuzz_parse() {
+        let nested_parens = format!("int i = {}1{};", "(".repeat(30), ")".repeat(30));
+        let nested_braces = format!("int f() {}", "{".repeat(30));
+        let inputs: &[&[u8]] = &[
+            b"",
+            b"}}}}",
+            b"{{{{",
+            b"int f() { if (1) { }",
+            b"+",
+            b"int i = ;",
+            b"int a[",
+            b"struct {",
+            b"typedef",
+            b"\"unterminated",
+            b"/*",
+            b"'",
+            b"#if",
+            b"#define f(x) f(x\nf(",
+            b"\xff\xfe\x00int",
+            nested_parens.as_bytes(),
+            nested_braces.as_bytes(),
+        ];
+        for input in inputs {
+            super::fuzz_parse(input);
+        }
+    }
+
+    #[test]
+    fn warnings_as_errors() {
+        use data::error::Warning;
+
+        let program = super::check_semantics("foo();\n", Opt::default());
+        assert!(program.result.is_ok());
+        assert!(program
+            .warnings
+            .iter()
+            .any(|w| w.data == Warning::ImplicitInt));
+
+        let opt = Opt {
+            warnings_as_errors: true,
+            ..Opt::default()
+        };
+        let program = super::check_semantics("foo();\n", opt);
+        assert!(program.warnings.is_empty());
+        let errs = program.result.err().unwrap();
+        assert!(errs
+            .iter()
+            .any(|err| err.data == data::Error::Warning(Warning::ImplicitInt)));
+
+        // warnings from the parser count too
+        let opt = Opt {
+            warnings_as_errors: true,
+            ..Opt::default()
+        };
+        let program = super::check_semantics("int f(int x) { if (x = 0); }\n", opt);
+        assert!(program.warnings.is_empty());
+        let errs = program.result.err().unwrap();
+        let assign = data::Error::Warning(Warning::AssignmentInCondition);
+        assert!(errs.iter().any(|err| err.data == assign));
+    }
+
+    #[test]
+    fn parser_warnings() {
+        use data::error::Warning;
+
+        let warnings = |src| {
+            let program = super::check_semantics(src, Opt::default());
+            assert!(program.result.is_ok(), "{} should compile", src);
+            let warnings = program.warnings.into_iter().map(|w| w.data);
+            warnings.collect::<Vec<_>>()
+        };
+        assert_eq!(
+            warnings("int f(int x) { if (x = 0); return x; }\n"),
+            vec![Warning::AssignmentInCondition]
+        );
+        assert_eq!(
+            warnings("int f() { end: }\n"),
+            vec![Warning::LabelAtEndOfBlock]
+        );
+        assert!(warnings("_Pragma(\"once\") int x;\n").contains(&Warning::IgnoredPragma));
+        assert!(warnings("[[unknown]] int x;\n").contains(&Warning::IgnoredAttribute));
+    }
+
+    #[test]
+    fn translation_unit() {
+        let src = "int x;\nint f() { return 1 +; }\n;\ntypedef int i; i y;\n";
+        let unit = super::parse_translation_unit(src, Opt::default());
+        // the error doesn't throw away the declarations around it
+        let decls: Vec<_> = unit.decls.iter().map(|d| d.data.to_string()).collect();
+        assert_eq!(decls.first().unwrap(), "int x;");
+        assert!(decls.contains(&"typedef int i;".to_string()));
+        assert_eq!(decls.last().unwrap(), "i y;");
+
+        let diagnostics: Vec<_> = unit.diagnostics.into_iter().map(|d| d.data).collect();
+        assert!(diagnostics[0].is_syntax_err());
+        // the extraneous semicolon
+        assert!(matches!(diagnostics.last(), Some(data::Error::Warning(_))));
+    }
+
+    #[test]
+    fn parse_in_parallel() {
+        use std::thread;
+
+        let diagnostics = |src: &'static str| {
+            thread::spawn(move || {
+                let program = super::parse(src, Opt::default());
+                let errs = program.result.err().unwrap_or_default();
+                let errs: Vec<_> = errs.into_iter().map(|e| e.data.to_string()).collect();
+                let warnings: Vec<_> = program
+                    .warnings
+                    .into_iter()
+                    .map(|w| w.data.to_string())
+                    .collect();
+                (errs, warnings)
+            })
+        };
+        let bad = diagnostics("int f() { return 1 +; }\n");
+        let good = diagnostics(";\nint x;\n");
+        let (bad_errs, bad_warnings) = bad.join().unwrap();
+        let (good_errs, good_warnings) = good.join().unwrap();
+        // each thread only sees its own diagnostics
+        assert_eq!(
+            bad_errs,
+            vec!["invalid syntax: expected variable, literal, or '('"]
+        );
+        assert!(bad_warnings.is_empty());
+        assert!(good_errs.is_empty());
+        assert_eq!(good_warnings, vec!["extraneous semicolon at top level"]);
+    }
 }
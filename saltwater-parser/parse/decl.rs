@@ -1,7 +1,7 @@
 use super::*;
 use crate::data::ast::{
-    self, Declaration, DeclarationSpecifier, Declarator, Expr, ExternalDeclaration, Initializer,
-    TypeName,
+    self, Attribute, Declaration, DeclarationSpecifier, Declarator, Expr, ExternalDeclaration,
+    Initializer, TypeName,
 };
 use crate::data::error::Warning;
 use crate::data::*;
@@ -15,6 +15,7 @@ enum InternalDeclaratorType {
     },
     Array {
         size: Option<Box<Expr>>,
+        qualifiers: Vec<DeclarationSpecifier>,
     },
     Function {
         params: Vec<TypeName>,
@@ -42,21 +43,56 @@ impl<I: Lexer> Parser<I> {
     /// ```
     /// <http://www.quut.com/c/ANSI-C-grammar-y.html#external_declaration>
     pub fn external_declaration(&mut self) -> SyntaxResult<Locatable<ExternalDeclaration>> {
+        let doc = self.doc_comment();
         let (specifiers, specifier_locations) = self.specifiers()?;
+        let attributes = mem::take(&mut self.attributes);
 
         // allow `int;`
         if let Some(token) = self.match_next(&Token::Semicolon) {
+            // `typedef int;` is an error, but `typedef struct s { int i; };` still declares `s`
+            let declares_tag = specifiers.iter().any(|s| {
+                matches!(
+                    s,
+                    DeclarationSpecifier::Struct(_)
+                        | DeclarationSpecifier::Union(_)
+                        | DeclarationSpecifier::Enum { .. }
+                )
+            });
+            let typedef = DeclarationSpecifier::Unit(crate::data::ast::UnitSpecifier::Typedef);
+            if specifiers.contains(&typedef) && !declares_tag {
+                return Err(token.location.with(SyntaxError::TypedefWithoutName));
+            }
             let location = token.location.maybe_merge(specifier_locations);
-            self.error_handler.warn(Warning::EmptyDeclaration, location);
+            // `struct s;` and `enum { A };` declare something, but `int;` and `struct { int i; };` don't
+            let declares_anything = specifiers.iter().any(|s| match s {
+                DeclarationSpecifier::Struct(s) | DeclarationSpecifier::Union(s) => {
+                    s.name.is_some()
+                }
+                DeclarationSpecifier::Enum { .. } => true,
+                _ => false,
+            });
+            if !declares_anything {
+                self.error_handler.warn(Warning::EmptyDeclaration, location);
+            }
             let empty_decl = ExternalDeclaration::Declaration(Declaration {
                 specifiers,
                 declarators: Vec::new(),
+                doc,
+                attributes: self.check_attributes(attributes, false),
             });
             return Ok(Locatable::new(empty_decl, location));
         }
 
-        let declarator = self.init_declarator()?;
+        let mut declarator = self.init_declarator()?;
         let mut location = declarator.location.maybe_merge(specifier_locations);
+        // int f(a, b) int a; double b; {}
+        if self.peek_token().map_or(false, Token::is_decl_specifier) {
+            if let ast::DeclaratorType::Function(func) = &mut declarator.data.declarator.decl {
+                if func.is_identifier_list() {
+                    self.kr_declarations(func, location)?;
+                }
+            }
+        }
         if self.peek_token() == Some(&Token::LeftBrace) {
             use crate::data::ast::{DeclaratorType, FunctionDefinition};
 
@@ -80,38 +116,150 @@ impl<I: Lexer> Parser<I> {
                 body: body.data,
                 specifiers,
                 declarator: func,
+                doc,
+                attributes: self.check_attributes(attributes, true),
             };
             return Ok(Locatable::new(ExternalDeclaration::Function(def), location));
         }
-        let mut decls = vec![declarator];
         let has_typedef = specifiers
             .iter()
             .any(|s| *s == DeclarationSpecifier::Unit(crate::data::ast::UnitSpecifier::Typedef));
+        // declare the typedef before looking ahead, so that the lookahead sees it:
+        // typedef int T
+        // T y;
+        if has_typedef {
+            self.declare_typedef(&declarator.data);
+        }
+        let mut decls = vec![declarator];
         while self.match_next(&Token::Semicolon).is_none() {
+            // int x
+            // int y;
+            if self.starts_declaration() {
+                let last = decls.last().unwrap().location;
+                self.error_handler
+                    .error(SyntaxError::MissingSemicolon, last);
+                break;
+            }
             self.expect(Token::Comma)?;
             let decl = self.init_declarator()?;
             location = location.merge(decl.location);
+            // int x, f() {}
+            let is_function = matches!(decl.data.declarator.decl, ast::DeclaratorType::Function(_));
+            if has_typedef {
+                self.declare_typedef(&decl.data);
+            }
             decls.push(decl);
-        }
-        if has_typedef {
-            // `int *;` is caught later
-            for id in decls.iter().filter_map(|d| d.data.declarator.id) {
-                self.typedefs.insert(id, ());
+            if is_function && self.peek_token() == Some(&Token::LeftBrace) {
+                let body = self.compound_statement()?;
+                self.error_handler
+                    .error(SyntaxError::FunctionDefinitionNotAllowed, body.location);
+                location = location.merge(body.location);
+                self.match_next(&Token::Semicolon);
+                break;
             }
         }
+        let is_function = decls
+            .iter()
+            .all(|d| matches!(d.data.declarator.decl, ast::DeclaratorType::Function(_)));
         let declaration = Declaration {
             specifiers,
             declarators: decls,
+            doc,
+            attributes: self.check_attributes(attributes, is_function),
         };
         Ok(Locatable::new(
             ExternalDeclaration::Declaration(declaration),
             location,
         ))
     }
+    /// Parse the name declared by `decl` as a type from now on.
+    fn declare_typedef(&mut self, decl: &ast::InitDeclarator) {
+        // `int *;` is caught later
+        if let Some(id) = decl.declarator.id {
+            self.typedefs.insert(id, ());
+        }
+    }
+    /// Report an error for each attribute that isn't allowed on a declaration.
+    ///
+    /// `[[noreturn]]` can only be applied to functions,
+    /// and `[[fallthrough]]` can only be applied to an empty statement.
+    fn check_attributes(
+        &mut self,
+        attributes: Vec<Locatable<Attribute>>,
+        is_function: bool,
+    ) -> Vec<Locatable<Attribute>> {
+        attributes
+            .into_iter()
+            .filter(|attr| {
+                let reason = match attr.data {
+                    Attribute::NoReturn if !is_function => "can only be applied to a function",
+                    Attribute::Fallthrough => "must be followed by a case label",
+                    _ => return true,
+                };
+                let err = SyntaxError::MisplacedAttribute(attr.data.clone(), reason);
+                self.error_handler.error(err, attr.location);
+                false
+            })
+            .collect()
+    }
+    /// The declaration list of an old-style (K&R) function definition:
+    /// `int f(a, b) int a; double b; {}`
+    ///
+    /// The declarations are merged into the parameter list,
+    /// so this is parsed the same as `int f(int a, double b) {}`.
+    /// Parameters that are never declared have no specifiers, so they default to `int`.
+    fn kr_declarations(
+        &mut self,
+        func: &mut ast::FunctionDeclarator,
+        location: Location,
+    ) -> SyntaxResult<()> {
+        if !self.kr_definitions {
+            self.error_handler
+                .error(SyntaxError::KRDefinition, location);
+        }
+        while self.peek_token().map_or(false, Token::is_decl_specifier) {
+            let (specifiers, _) = self.specifiers()?;
+            let attributes = mem::take(&mut self.attributes);
+            self.check_attributes(attributes, false);
+            loop {
+                let decl = self.declarator(false)?;
+                let decl =
+                    decl.ok_or_else(|| self.last_location.with(SyntaxError::ExpectedDeclarator))?;
+                let location = decl.location;
+                let declarator = decl.data.parse_declarator();
+                let param = func
+                    .params
+                    .iter_mut()
+                    .find(|param| param.declarator.id == declarator.id);
+                match (param, declarator.id) {
+                    (Some(param), _) => {
+                        param.specifiers = specifiers.clone();
+                        param.declarator = declarator;
+                    }
+                    (None, Some(id)) => {
+                        let err = SyntaxError::NoSuchParameter(id);
+                        self.error_handler.error(err, location);
+                    }
+                    // int f(a) int *; {}
+                    (None, None) => {
+                        self.error_handler
+                            .error(SyntaxError::ExpectedDeclarator, location);
+                    }
+                }
+                if self.match_next(&Token::Comma).is_none() {
+                    break;
+                }
+            }
+            self.expect(Token::Semicolon)?;
+        }
+        Ok(())
+    }
     pub fn type_name(&mut self) -> SyntaxResult<Locatable<TypeName>> {
         use crate::ast::DeclaratorType;
 
         let (specifiers, specifier_locations) = self.specifiers()?;
+        let attributes = mem::take(&mut self.attributes);
+        self.check_attributes(attributes, false);
         let maybe_declarator = self.declarator(true)?;
         let (location, declarator) = match maybe_declarator {
             None => (
@@ -143,7 +291,18 @@ impl<I: Lexer> Parser<I> {
         let mut specifiers = Vec::new();
         let mut all_locs = None;
         let mut seen_typedef = false;
-        while let Some(&Token::Keyword(keyword)) = self.peek_token() {
+        // where each basic type was first specified
+        let mut type_locations: Vec<(ast::UnitSpecifier, Location)> = Vec::new();
+        loop {
+            let keyword = match self.peek_significant() {
+                Some(&Token::Keyword(keyword)) => keyword,
+                // `typedef int T\nT x;`: `T` was lexed before it was declared
+                Some(&Token::Id(id)) => match self.typedefs.get(&id) {
+                    Some(_) => Keyword::UserTypedef(id),
+                    None => break,
+                },
+                _ => break,
+            };
             let location = self.next_token().unwrap().location;
             let spec = match keyword {
                 Keyword::Struct => self.struct_specifier(true, location)?,
@@ -168,6 +327,23 @@ impl<I: Lexer> Parser<I> {
             all_locs = all_locs.map_or(Some(spec.location), |existing: Location| {
                 Some(existing.merge(spec.location))
             });
+            // `char char i;`
+            // this is caught here instead of in the analyzer so the error points to the duplicate
+            if let DeclarationSpecifier::Unit(unit) = spec.data {
+                if unit.is_type() && unit != ast::UnitSpecifier::Long {
+                    let previous = type_locations.iter().find(|&&(u, _)| u == unit);
+                    if let Some(&(_, previous)) = previous {
+                        let err = SemanticError::InvalidSpecifier {
+                            existing: spec.data.clone(),
+                            new: spec.data,
+                            previous,
+                        };
+                        self.error_handler.error(err, spec.location);
+                        continue;
+                    }
+                    type_locations.push((unit, spec.location));
+                }
+            }
             specifiers.push(spec.data);
         }
         Ok((specifiers, all_locs))
@@ -238,6 +414,8 @@ impl<I: Lexer> Parser<I> {
     fn struct_declaration_list(&mut self) -> SyntaxResult<Locatable<ast::StructDeclarationList>> {
         //use data::lex::LocationTrait;
         let (specifiers, mut spec_location) = self.specifiers()?;
+        let attributes = mem::take(&mut self.attributes);
+        self.check_attributes(attributes, false);
         let mut declarators = Vec::new();
         let location = loop {
             if let Some(token) = self.match_next(&Token::Semicolon) {
@@ -260,6 +438,20 @@ impl<I: Lexer> Parser<I> {
             } else {
                 None
             };
+            // struct s { int f() {} };
+            if self.peek_token() == Some(&Token::LeftBrace) {
+                let body = self.compound_statement()?;
+                self.error_handler
+                    .error(SyntaxError::FunctionDefinitionNotAllowed, body.location);
+                declarators.push(ast::StructDeclarator {
+                    decl,
+                    bitfield: None,
+                });
+                break match self.match_next(&Token::Semicolon) {
+                    Some(token) => token.location,
+                    None => body.location,
+                };
+            }
             let bitfield = if let Some(token) = self.match_next(&Token::Colon) {
                 let size = self.ternary_expr()?;
                 spec_location = Some(token.location.merge(size.location));
@@ -379,6 +571,18 @@ impl<I: Lexer> Parser<I> {
             })
         }
     }
+    fn is_function(decl: &Option<Locatable<InternalDeclarator>>) -> bool {
+        matches!(
+            decl,
+            Some(Locatable {
+                data: InternalDeclarator {
+                    current: InternalDeclaratorType::Function { .. },
+                    ..
+                },
+                ..
+            })
+        )
+    }
     fn declarator(
         &mut self,
         allow_abstract: bool,
@@ -394,18 +598,9 @@ impl<I: Lexer> Parser<I> {
         while let Some(Locatable { mut location, .. }) = self.match_next(&Token::Star) {
             let mut qualifiers = Vec::new();
             // *const volatile p
-            while let Some(Locatable {
-                location: keyword_loc,
-                data: Token::Keyword(keyword),
-            }) = self.match_any(&[
-                &Token::Keyword(Keyword::Const),
-                &Token::Keyword(Keyword::Volatile),
-                &Token::Keyword(Keyword::Restrict),
-                &Token::Keyword(Keyword::Atomic),
-                &Token::Keyword(Keyword::ThreadLocal),
-            ]) {
-                location = location.merge(keyword_loc);
-                qualifiers.push(keyword.try_into().unwrap());
+            while let Some(qualifier) = self.match_qualifier() {
+                location = location.merge(qualifier.location);
+                qualifiers.push(qualifier.data);
             }
             let current = Locatable::new(InternalDeclaratorType::Pointer { qualifiers }, location);
             pointer_decls.push(current);
@@ -416,6 +611,21 @@ impl<I: Lexer> Parser<I> {
         }
         Ok(decl)
     }
+    fn match_qualifier(&mut self) -> Option<Locatable<DeclarationSpecifier>> {
+        match self.match_any(&[
+            &Token::Keyword(Keyword::Const),
+            &Token::Keyword(Keyword::Volatile),
+            &Token::Keyword(Keyword::Restrict),
+            &Token::Keyword(Keyword::Atomic),
+            &Token::Keyword(Keyword::ThreadLocal),
+        ])? {
+            Locatable {
+                data: Token::Keyword(keyword),
+                location,
+            } => Some(Locatable::new(keyword.try_into().unwrap(), location)),
+            _ => unreachable!("match_any should only return qualifiers"),
+        }
+    }
     /*
      * Originally written as follows:
      * direct_declarator
@@ -466,6 +676,22 @@ impl<I: Lexer> Parser<I> {
         allow_abstract: bool,
     ) -> SyntaxResult<Option<Locatable<InternalDeclarator>>> {
         let _guard = self.recursion_check();
+        // typedef int (static f)(void);
+        // storage classes are only allowed at the start of a declaration, but it's easy to recover
+        if let Some(storage_class) = self.match_keywords(&[
+            Keyword::Static,
+            Keyword::Extern,
+            Keyword::Auto,
+            Keyword::Register,
+            Keyword::Typedef,
+        ]) {
+            let err = SyntaxError::StorageClassInDeclarator(storage_class.data);
+            self.error_handler.error(err, storage_class.location);
+            // typedef int (static *f)(void);
+            if self.peek_token() == Some(&Token::Star) {
+                return self.declarator(allow_abstract);
+            }
+        }
         // we'll pass this to postfix_type in just a second
         // if None, we didn't find an ID
         // should only happen if allow_abstract is true
@@ -520,18 +746,11 @@ impl<I: Lexer> Parser<I> {
                 }
             }
             _ if allow_abstract => None,
-            Some(x) => {
-                let err = Err(Locatable::new(
-                    SyntaxError::Generic(format!("expected variable name or '(', got '{}'", x)),
-                    self.next_location(),
-                ));
-                self.panic();
-                return err;
-            }
-            None => {
-                return Err(self.next_location().with(SyntaxError::Generic(
-                    "expected variable name or '(', got <end-of-of-file>".into(),
-                )));
+            // int 1;
+            _ => {
+                let choices = [Token::LeftParen, Token::Star, Token::Id(Default::default())];
+                let err = self.expect_any(&choices);
+                return Err(err.expect_err("declarator should have already been handled"));
             }
         };
         self.postfix_type(decl, allow_abstract)
@@ -556,25 +775,56 @@ impl<I: Lexer> Parser<I> {
     ) -> SyntaxResult<Option<Locatable<InternalDeclarator>>> {
         while let Some(data) = self.peek_token() {
             let current = match data {
+                // int f(void)[static 10];
+                // functions can't return arrays, and trying to parse the array suffix
+                // would only report errors about `static` on top of this one
+                Token::LeftBracket if Self::is_function(&prefix) => {
+                    let start = self.next_location();
+                    let end = self.skip_balanced().unwrap_or(start);
+                    let err = SyntaxError::FunctionReturningArray;
+                    self.error_handler.error(err, start.merge(end));
+                    break;
+                }
                 // Array; Specified in section 6.7.6.2 of the C11 spec
                 Token::LeftBracket => {
                     self.expect(Token::LeftBracket).unwrap();
-                    if let Some(token) = self.match_next(&Token::Keyword(Keyword::Static)) {
+                    // int f(int a[static const 10])
+                    let mut qualifiers = Vec::new();
+                    loop {
+                        let (error, location) = if let Some(token) =
+                            self.match_next(&Token::Keyword(Keyword::Static))
+                        {
+                            (SyntaxError::StaticInConcreteArray, token.location)
+                        } else if let Some(qualifier) = self.match_qualifier() {
+                            qualifiers.push(qualifier.data);
+                            (SyntaxError::QualifierInConcreteArray, qualifier.location)
+                        } else {
+                            break;
+                        };
                         if !allow_abstract {
-                            self.error_handler.push_back(Locatable::new(
-                                SyntaxError::StaticInConcreteArray,
-                                token.location,
-                            ));
+                            self.error_handler
+                                .push_back(Locatable::new(error, location));
                         }
                     }
                     let (size, location) =
                         if let Some(token) = self.match_next(&Token::RightBracket) {
                             (None, token.location)
                         } else {
-                            let expr = Box::new(self.expr()?);
-                            (Some(expr), self.expect(Token::RightBracket)?.location)
+                            // 6.7.6.2: the size is an assignment-expression, so `int a[1, 2]` is invalid
+                            // `int a[1 +]`: keep going so the rest of the declaration is still parsed
+                            let expr = self.assignment_expr().unwrap_or_else(|err| {
+                                let location = err.location;
+                                self.error_handler.push_back(err);
+                                location.with(ast::ExprType::Error)
+                            });
+                            let expr = Box::new(expr);
+                            let location = match self.match_next(&Token::RightBracket) {
+                                Some(token) => token.location,
+                                None => self.missing_right_bracket(),
+                            };
+                            (Some(expr), location)
                         };
-                    Locatable::new(InternalDeclaratorType::Array { size }, location)
+                    Locatable::new(InternalDeclaratorType::Array { size, qualifiers }, location)
                 }
                 Token::LeftParen => self.parameter_type_list()?,
                 _ => break,
@@ -583,6 +833,24 @@ impl<I: Lexer> Parser<I> {
         }
         Ok(prefix)
     }
+    /// `int a[10 b;`: report the missing `]` and skip to the end of the declarator,
+    /// so that the rest of the declaration is still parsed.
+    fn missing_right_bracket(&mut self) -> Location {
+        let location = self.next_location();
+        let got = self.peek_token().cloned();
+        let err = SyntaxError::ExpectedOneOf(vec![Token::RightBracket], got);
+        self.error_handler.error(err, location);
+        while let Some(token) = self.peek_token() {
+            match token {
+                Token::Semicolon | Token::Comma | Token::RightParen | Token::LeftBrace => break,
+                _ if *token == Token::EQUAL => break,
+                _ => {
+                    self.next_token();
+                }
+            }
+        }
+        location
+    }
     /*
      * function parameters
      * reference grammar:
@@ -662,12 +930,17 @@ impl<I: Lexer> Parser<I> {
         let _guard = self.recursion_check();
         let mut elems = vec![];
         while self.match_next(&Token::RightBrace).is_none() {
+            let designators = self.designation()?;
             let next = if self.match_next(&Token::LeftBrace).is_some() {
                 self.aggregate_initializer()?
             } else {
                 // scalar
                 self.initializer()?
             };
+            let next = match designators {
+                Some(designators) => Initializer::Designated(designators, Box::new(next)),
+                None => next,
+            };
             elems.push(next);
             // NOTE: this allows trailing commas
             if self.match_next(&Token::Comma).is_none() {
@@ -677,6 +950,74 @@ impl<I: Lexer> Parser<I> {
         }
         Ok(Initializer::Aggregate(elems))
     }
+
+    /// ```yacc
+    /// designation
+    /// : designator_list '='
+    /// | identifier ':' /* obsolete GNU extension */
+    /// ;
+    ///
+    /// designator
+    /// : '[' constant_expression ']'
+    /// | '[' constant_expression '...' constant_expression ']' /* GNU extension */
+    /// | '.' identifier
+    /// ;
+    /// ```
+    ///
+    /// Returns `None` if there was no designation.
+    fn designation(&mut self) -> SyntaxResult<Option<Vec<Locatable<ast::Designator>>>> {
+        use ast::Designator;
+
+        // { x: 1 }
+        if let Some(&Token::Id(name)) = self.peek_token() {
+            if self.peek_next_token() == Some(&Token::Colon) {
+                let start = self.next_token().unwrap().location;
+                let location = start.merge(self.next_token().unwrap().location);
+                if self.gnu_extensions {
+                    self.error_handler
+                        .warn(Warning::ObsoleteDesignator(name), location);
+                } else {
+                    let err = SyntaxError::ObsoleteDesignator(name);
+                    self.error_handler.error(err, location);
+                }
+                return Ok(Some(vec![location.with(Designator::Field(name))]));
+            }
+        }
+        // { .x = 1 } or { [0] = 1 }
+        let mut designators = Vec::new();
+        loop {
+            let designator = if let Some(dot) = self.match_next(&Token::Dot) {
+                let name = self.expect_id()?;
+                dot.location
+                    .merge(name.location)
+                    .with(Designator::Field(name.data))
+            } else if let Some(bracket) = self.match_next(&Token::LeftBracket) {
+                let index = self.ternary_expr()?;
+                // [0 ... 3]
+                let designator = if self.match_next(&Token::Ellipsis).is_some() {
+                    let high = self.ternary_expr()?;
+                    if !self.gnu_extensions {
+                        let location = index.location.merge(high.location);
+                        self.error_handler
+                            .error(SyntaxError::RangeDesignator, location);
+                    }
+                    Designator::Range(index, high)
+                } else {
+                    Designator::Index(index)
+                };
+                let end = self.expect(Token::RightBracket)?.location;
+                bracket.location.merge(end).with(designator)
+            } else {
+                break;
+            };
+            designators.push(designator);
+        }
+        if designators.is_empty() {
+            return Ok(None);
+        }
+        self.expect(Token::EQUAL)?;
+        Ok(Some(designators))
+    }
 }
 
 impl InternalDeclarator {
@@ -697,9 +1038,10 @@ impl InternalDeclarator {
                     to: Box::new(current),
                     qualifiers,
                 },
-                Array { size } => DeclaratorType::Array {
+                Array { size, qualifiers } => DeclaratorType::Array {
                     of: Box::new(current),
                     size,
+                    qualifiers,
                 },
                 Function { params, varargs } => DeclaratorType::from(ast::FunctionDeclarator {
                     return_type: Box::new(current),
@@ -733,7 +1075,7 @@ impl TryFrom<Keyword> for DeclarationSpecifier {
             Const, Volatile, Restrict, Atomic, ThreadLocal,
             Unsigned, Signed,
             Bool, Char, Short, Int, Long, Float, Double, Void,
-            Complex, Imaginary, VaList,
+            Complex, Imaginary, VaList, AutoType,
             Extern, Static, Auto, Register, Typedef,
             Inline, NoReturn,
         )
@@ -757,7 +1099,7 @@ impl Keyword {
             // type specifier
             Unsigned | Signed | Bool | Char | Short | Int | Long | Float | Double | Void
             // complex type specifier
-            | Struct | Union | Enum | VaList | Complex | Imaginary
+            | Struct | Union | Enum | VaList | Complex | Imaginary | AutoType
             // user-defined type
             | UserTypedef(_)
             // storage class
@@ -774,6 +1116,7 @@ impl Keyword {
 #[cfg(test)]
 pub(crate) mod test {
     use crate::data::ast::*;
+    use crate::data::error::Warning;
     use crate::data::*;
     use crate::parse::test::*;
 
@@ -799,6 +1142,28 @@ pub(crate) mod test {
     pub(crate) fn assert_no_change(s: &str) {
         assert_display(s, s);
     }
+    /// Parse the first declaration in `input`, ignoring any errors.
+    fn declaration(input: &str) -> Declaration {
+        match parser(input).external_declaration().unwrap().data {
+            ExternalDeclaration::Declaration(decl) => decl,
+            other => panic!("expected a declaration, got {}", other),
+        }
+    }
+    /// Parse the size of the first declarator in `input`, which must be an array.
+    fn array_size(input: &str) -> Expr {
+        match declaration(input)
+            .declarators
+            .remove(0)
+            .data
+            .declarator
+            .decl
+        {
+            DeclaratorType::Array {
+                size: Some(size), ..
+            } => *size,
+            other => panic!("expected an array, got {:?}", other),
+        }
+    }
 
     #[test]
     fn username() {
@@ -832,6 +1197,602 @@ pub(crate) mod test {
         assert!(decl("struct s { int *; };").is_err());
     }
     #[test]
+    fn test_storage_class_in_declarator() {
+        use crate::data::lex::Keyword;
+
+        let err = SyntaxError::StorageClassInDeclarator(Keyword::Static);
+        assert_eq!(
+            decl("typedef int (static f)(void);").unwrap_err().data,
+            Error::Syntax(err)
+        );
+        assert!(decl("int *extern p;").is_err());
+        // recover by ignoring the storage class
+        let mut p = parser("typedef int (static f)(void);");
+        let recovered = p.external_declaration().unwrap();
+        assert_eq!(
+            recovered.data.to_string(),
+            display("typedef int (f)(void);")
+        );
+        let mut p = parser("typedef int (static *f)(void);");
+        let recovered = p.external_declaration().unwrap();
+        assert_eq!(
+            recovered.data.to_string(),
+            display("typedef int (*f)(void);")
+        );
+        // a misplaced storage class followed by a pointer at the end of the file
+        assert!(decl("(typedef *").is_err());
+    }
+    #[test]
+    fn test_qualified_array_parameter() {
+        assert_no_change("int f(int a[const restrict 10]);");
+        assert_no_change("int f(int a[volatile]);");
+        assert_eq!(
+            decl("int a[const 1];").unwrap_err().data,
+            Error::Syntax(SyntaxError::QualifierInConcreteArray)
+        );
+    }
+    #[test]
+    fn test_array_size_is_not_comma_expr() {
+        assert!(decl("int x[1, 2];").is_err());
+        assert!(decl("int x[(1, 2)];").is_ok());
+    }
+    #[test]
+    fn test_gnu_keyword_aliases() {
+        assert_same("__const__ int x;", "const int x;");
+        assert_same("__inline__ int f();", "inline int f();");
+        assert_same("__signed__ char *__restrict p;", "signed char *restrict p;");
+        assert_same("__volatile int x;", "volatile int x;");
+    }
+    #[test]
+    fn test_typedef_without_name() {
+        let err = Error::Syntax(SyntaxError::TypedefWithoutName);
+        assert_eq!(decl("typedef;").unwrap_err().data, err);
+        assert_eq!(decl("typedef int;").unwrap_err().data, err);
+        // points at the semicolon
+        let location = decl("typedef int;").unwrap_err().location;
+        assert_eq!(location.span, (11..12).into());
+        assert!(decl("typedef struct s { int i; };").is_ok());
+    }
+    #[test]
+    fn test_typedef_of_incomplete_struct() {
+        // the parser only cares that `T` is a typedef, not whether its type is complete
+        let decls: Vec<_> =
+            parser("typedef struct S T; T *p; T x; struct S { T *next; };").collect();
+        assert_eq!(decls.len(), 4);
+        assert!(decls.iter().all(Result::is_ok));
+    }
+    #[test]
+    fn test_kr_definition() {
+        let kr = |s| {
+            let mut p = parser(s).kr_definitions(true);
+            let def = p.external_declaration().map_err(CompileError::from);
+            match p.error_handler.pop_front() {
+                Some(err) => Err(err),
+                None => def,
+            }
+        };
+        let def = kr("int f(a, b) int a; double b; { return a; }").unwrap();
+        assert_eq!(
+            def.data.to_string(),
+            display("int f(int a, double b) { return a; }")
+        );
+        let def = kr("int f(a, b, c) char *b, c; {}").unwrap();
+        assert_eq!(
+            def.data.to_string(),
+            display("int f(a, char *b, char c) {}")
+        );
+        assert_eq!(
+            kr("int f(a) int b; {}").unwrap_err().data,
+            SyntaxError::NoSuchParameter("b".into()).into()
+        );
+        // without the flag, it's still parsed but is an error
+        assert_eq!(
+            decl("int f(a) int a; {}").unwrap_err().data,
+            SyntaxError::KRDefinition.into()
+        );
+        // prototypes are unaffected
+        assert!(kr("int f(int a) {}").is_ok());
+    }
+    #[test]
+    fn test_expected_declarator() {
+        let err = decl("int 1;").unwrap_err().data;
+        let expected = SyntaxError::ExpectedOneOf(
+            vec![Token::LeftParen, Token::Star, Token::Id(Default::default())],
+            Some(LiteralToken::Int(arcstr::literal_substr!("1")).into()),
+        );
+        assert_eq!(err, Error::Syntax(expected));
+        assert_eq!(
+            err.to_string(),
+            "invalid syntax: expected one of '(', '*', identifier; got '1'"
+        );
+    }
+    #[test]
+    fn test_designated_initializer() {
+        let init = |s| {
+            let display = display(s);
+            display[display.find('{').unwrap()..].to_string()
+        };
+        assert_eq!(
+            init("struct s a = { .x = 1, .y = 2 };"),
+            "{ .x = 1, .y = 2 };"
+        );
+        assert_no_change("int a[] = { [0] = 1, 2, [4] = 3 };");
+        assert_eq!(
+            init("struct s a = { .p[1].x = 1, .q = { .y = 2 } };"),
+            "{ .p[1].x = 1, .q = { .y = 2 } };"
+        );
+        assert!(decl("int a[] = { [0] 1 };").is_err());
+        assert!(decl("int a[] = { [] = 1 };").is_err());
+    }
+    #[test]
+    fn test_gnu_field_designator() {
+        let err = SyntaxError::ObsoleteDesignator("x".into());
+        assert_eq!(
+            decl("struct s a = { x: 1, y: 2 };").unwrap_err().data,
+            Error::Syntax(err)
+        );
+
+        let mut p = parser("struct s a = { x: 1, y: 2 };").gnu_extensions(true);
+        let gnu = p.external_declaration().unwrap();
+        assert!(p.error_handler.pop_front().is_none());
+        // mapped to the standard form
+        assert_eq!(
+            gnu.data.to_string(),
+            display("struct s a = { .x = 1, .y = 2 };")
+        );
+        let warnings: Vec<_> = p.warnings().into_iter().map(|w| w.data).collect();
+        assert_eq!(
+            warnings,
+            vec![
+                Warning::ObsoleteDesignator("x".into()),
+                Warning::ObsoleteDesignator("y".into())
+            ]
+        );
+        assert_eq!(
+            warnings[0].to_string(),
+            "use of GNU old-style field designator, use '.x =' instead"
+        );
+    }
+    #[test]
+    fn test_multidimensional_initializer() {
+        // the parser keeps the braces as written; the analyzer reshapes them to fit the type
+        let shape = |s| {
+            fn braces(init: &Initializer) -> String {
+                match init {
+                    Initializer::Scalar(_) => "x".into(),
+                    Initializer::Aggregate(list) => {
+                        let inner: Vec<_> = list.iter().map(braces).collect();
+                        format!("{{{}}}", inner.join(","))
+                    }
+                    Initializer::Designated(_, init) => braces(init),
+                }
+            }
+            match decl(s).unwrap().data {
+                ExternalDeclaration::Declaration(decl) => {
+                    braces(decl.declarators[0].data.init.as_ref().unwrap())
+                }
+                _ => panic!("expected a declaration"),
+            }
+        };
+        assert_eq!(shape("int m[2][3] = {1,2,3,4,5,6};"), "{x,x,x,x,x,x}");
+        assert_eq!(
+            shape("int m[2][3] = {{1,2,3},{4,5,6}};"),
+            "{{x,x,x},{x,x,x}}"
+        );
+        assert_eq!(shape("int m[2][3] = {{1,2,3}, 4,5,6};"), "{{x,x,x},x,x,x}");
+        assert_eq!(
+            shape("int m[2][2][2] = {{1,2}, 3,4, {{5}}};"),
+            "{{x,x},x,x,{{x}}}"
+        );
+    }
+    #[test]
+    fn test_designator_range() {
+        let range = "int a[5] = { [1 ... 3] = 7 };";
+        assert_eq!(
+            decl(range).unwrap_err().data,
+            Error::Syntax(SyntaxError::RangeDesignator)
+        );
+
+        let mut p = parser(range).gnu_extensions(true);
+        let gnu = p.external_declaration().unwrap();
+        assert!(p.error_handler.pop_front().is_none());
+        assert_eq!(gnu.data.to_string(), range);
+        // the bounds are checked by the analyzer, since they can be any constant expression
+        let mut p = parser("int a[5] = { [N ... 1] = 7 };").gnu_extensions(true);
+        assert!(p.external_declaration().is_ok());
+        assert!(p.error_handler.pop_front().is_none());
+    }
+    #[test]
+    fn test_typedef_of_pointer_to_function_returning_array_pointer() {
+        // cdecl: declare T as pointer to function (void) returning pointer to array 3 of int
+        assert_no_change("typedef int (*(*T)(void))[3];");
+    }
+    #[test]
+    fn test_array_size_ternary() {
+        assert_display("int a[c ? 4 : 8];", "int a[(c) ? (4) : (8)];");
+        assert_display(
+            "int a[x ? y ? 1 : 2 : 3];",
+            "int a[(x) ? ((y) ? (1) : (2)) : (3)];",
+        );
+    }
+    #[test]
+    fn test_float_initializers() {
+        let decl = declaration("double a[] = {2.75, 1e3};");
+        let elems: Vec<_> = match &decl.declarators[0].data.init {
+            Some(Initializer::Aggregate(elems)) => elems
+                .iter()
+                .map(|elem| match elem {
+                    Initializer::Scalar(expr) => expr.data.clone(),
+                    other => panic!("expected a scalar initializer, got {:?}", other),
+                })
+                .collect(),
+            other => panic!("expected an aggregate initializer, got {:?}", other),
+        };
+        let float = |f| ExprType::Literal(LiteralValue::Float(f));
+        assert_eq!(elems, vec![float(2.75), float(1000.0)]);
+    }
+    #[test]
+    fn test_imaginary_initializer() {
+        let decl = declaration("_Complex double z = 1.0i;");
+        match &decl.declarators[0].data.init {
+            Some(Initializer::Scalar(init)) => assert_eq!(init.data, ExprType::Imaginary(1.0)),
+            other => panic!("expected a scalar initializer, got {:?}", other),
+        }
+        assert_display("_Complex double z = 2.5fi;", "_Complex double z = 2.5i;");
+    }
+    #[test]
+    fn test_alignas_identifier() {
+        // `alignas` is only a keyword in C23
+        assert_no_change("int alignas;");
+        assert_no_change("int alignof(int alignas);");
+    }
+    #[test]
+    fn test_auto_type() {
+        assert_no_change("__auto_type x = 5;");
+        assert_no_change("const __auto_type y = x;");
+    }
+    #[test]
+    fn test_gnu_thread() {
+        use crate::{Parser, PreProcessorBuilder};
+
+        let gnu = |s| {
+            let cpp = PreProcessorBuilder::new(s).gnu_extensions(true).build();
+            Parser::new(cpp, false).external_declaration()
+        };
+        let gnu_display = |s| gnu(s).unwrap().data.to_string();
+        assert_eq!(
+            gnu_display("static __thread int x;"),
+            "static _Thread_local int x;"
+        );
+        assert_eq!(gnu_display("__thread int y;"), "_Thread_local int y;");
+        assert_eq!(
+            gnu("extern __thread int z;").unwrap(),
+            decl("extern _Thread_local int z;").unwrap()
+        );
+        // otherwise it's an ordinary identifier
+        assert!(decl("__thread int y;").is_err());
+    }
+    #[test]
+    fn test_array_size_literal() {
+        // the size keeps both the value and the location of the literal
+        let sizes = [
+            ("int a[5];", 5, (6, 7)),
+            ("int b[0x10];", 16, (6, 10)),
+            ("int c[ 12 ];", 12, (7, 9)),
+        ];
+        for &(input, expected, (start, end)) in &sizes {
+            let size = array_size(input);
+            assert_eq!(size.data, ExprType::Literal(LiteralValue::Int(expected)));
+            assert_eq!(size.location.span, (start..end).into());
+        }
+    }
+    #[test]
+    fn test_array_size_identifier() {
+        let size = array_size("int a[N];");
+        assert_eq!(size.data, ExprType::Id("N".into()));
+        assert_eq!(size.location.span, (6..7).into());
+    }
+    #[test]
+    fn test_array_size_precedence() {
+        let lit = |n| Box::new(Location::default().with(ExprType::Literal(LiteralValue::Int(n))));
+        let binary = |op: fn(Box<Expr>, Box<Expr>) -> ExprType, left, right| {
+            Box::new(Location::default().with(op(left, right)))
+        };
+
+        // (2 * 3) + 4
+        let expected = binary(ExprType::Add, binary(ExprType::Mul, lit(2), lit(3)), lit(4));
+        assert_eq!(array_size("int a[2*3+4];"), *expected);
+        // (10 - 4) - 3
+        let expected = binary(
+            ExprType::Sub,
+            binary(ExprType::Sub, lit(10), lit(4)),
+            lit(3),
+        );
+        assert_eq!(array_size("int b[10-4-3];"), *expected);
+        // 2 + ((3 * 4) % 5)
+        let expected = binary(
+            ExprType::Add,
+            lit(2),
+            binary(ExprType::Mod, binary(ExprType::Mul, lit(3), lit(4)), lit(5)),
+        );
+        assert_eq!(array_size("int c[2+3*4%5];"), *expected);
+        // parentheses group like an atom
+        let expected = binary(ExprType::Mul, binary(ExprType::Add, lit(1), lit(2)), lit(3));
+        assert_eq!(array_size("int d[(1 + 2) * 3];"), *expected);
+        let expected = binary(ExprType::Sub, lit(8), binary(ExprType::Sub, lit(4), lit(2)));
+        assert_eq!(array_size("int e[8 - ((4) - 2)];"), *expected);
+        // unary operators bind tighter than multiplication
+        let unary =
+            |op: fn(Box<Expr>) -> ExprType, inner| Box::new(Location::default().with(op(inner)));
+        assert_eq!(array_size("int f[-1];"), *unary(ExprType::Negate, lit(1)));
+        assert_eq!(
+            array_size("int g[- -5];"),
+            *unary(ExprType::Negate, unary(ExprType::Negate, lit(5)))
+        );
+        assert_eq!(
+            array_size("int h[!0];"),
+            *unary(ExprType::LogicalNot, lit(0))
+        );
+        assert_eq!(
+            array_size("int i[~0];"),
+            *unary(ExprType::BitwiseNot, lit(0))
+        );
+        let expected = binary(
+            ExprType::Mul,
+            unary(ExprType::UnaryPlus, lit(2)),
+            unary(ExprType::Negate, lit(3)),
+        );
+        assert_eq!(array_size("int j[+2 * -3];"), *expected);
+        // shifts bind more loosely than addition
+        let shift =
+            |val, by, left| Box::new(Location::default().with(ExprType::Shift(val, by, left)));
+        assert_eq!(
+            array_size("int buf[1 << 10];"),
+            *shift(lit(1), lit(10), true)
+        );
+        let expected = shift(lit(1), binary(ExprType::Add, lit(10), lit(1)), true);
+        assert_eq!(array_size("int k[1 << 10 + 1];"), *expected);
+        let expected = shift(shift(lit(64), lit(2), false), lit(1), false);
+        assert_eq!(array_size("int l[64 >> 2 >> 1];"), *expected);
+        // & above ^ above |
+        let expected = binary(
+            ExprType::BitwiseOr,
+            lit(1),
+            binary(
+                ExprType::Xor,
+                lit(2),
+                binary(ExprType::BitwiseAnd, lit(3), lit(4)),
+            ),
+        );
+        assert_eq!(array_size("int m[1 | 2 ^ 3 & 4];"), *expected);
+        // and all of them below shifts
+        let expected = binary(ExprType::BitwiseAnd, shift(lit(1), lit(4), true), lit(3));
+        assert_eq!(array_size("int n[1 << 4 & 3];"), *expected);
+    }
+    #[test]
+    fn test_missing_right_bracket() {
+        let mut p = parser("int a[10 b; int c;");
+        assert_eq!(
+            p.external_declaration().unwrap().data.to_string(),
+            "int a[10];"
+        );
+        let err = p.error_handler.pop_front().unwrap();
+        let expected =
+            SyntaxError::ExpectedOneOf(vec![Token::RightBracket], Some(Token::Id("b".into())));
+        assert_eq!(err.data, Error::Syntax(expected));
+        // no cascading errors
+        assert!(p.error_handler.pop_front().is_none());
+        assert_eq!(p.external_declaration().unwrap().data.to_string(), "int c;");
+        assert!(p.error_handler.pop_front().is_none());
+        assert!(p.is_empty());
+
+        // later declarators are still parsed
+        let mut p = parser("int a[10 b c, d;");
+        match p.external_declaration().unwrap().data {
+            ExternalDeclaration::Declaration(decl) => assert_eq!(decl.declarators.len(), 2),
+            other => panic!("expected a declaration, got {}", other),
+        }
+        assert!(p.error_handler.pop_front().is_some());
+        assert!(p.error_handler.pop_front().is_none());
+    }
+    #[test]
+    fn test_array_size_error() {
+        let input = "int a[1 +]; int b;";
+        assert_eq!(array_size(input).data, ExprType::Error);
+
+        let mut p = parser(input);
+        assert!(p.external_declaration().is_ok());
+        assert!(p.error_handler.pop_front().is_some());
+        assert!(p.error_handler.pop_front().is_none());
+        // the next declaration is unaffected
+        assert_eq!(p.external_declaration().unwrap().data.to_string(), "int b;");
+        assert!(p.error_handler.pop_front().is_none());
+    }
+    #[test]
+    fn test_empty_declaration() {
+        let warnings = |s| {
+            let mut p = parser(s);
+            assert!(p.external_declaration().is_ok());
+            p.warnings().into_iter().map(|w| w.data).collect::<Vec<_>>()
+        };
+        assert_eq!(warnings("int;"), vec![Warning::EmptyDeclaration]);
+        assert_eq!(
+            warnings("struct { int x; };"),
+            vec![Warning::EmptyDeclaration]
+        );
+        assert!(warnings("struct S;").is_empty());
+        assert!(warnings("struct S { int x; };").is_empty());
+        assert!(warnings("enum { A };").is_empty());
+    }
+    #[test]
+    fn test_duplicate_basic_type() {
+        let cases = [
+            ("char char i;", "char", 5),
+            ("int int i;", "int", 4),
+            ("int const int i;", "int", 10),
+        ];
+        for &(src, spec, start) in &cases {
+            let err = decl(src).unwrap_err();
+            assert_eq!(
+                err.data.to_string(),
+                format!(
+                    "invalid program: cannot combine '{0}' specifier with previous '{0}' type specifier",
+                    spec
+                )
+            );
+            // points to the second keyword, not the whole declaration
+            assert_eq!(err.location.span, (start..start + spec.len() as u32).into());
+            // and remembers the first
+            let first = src.find(spec).unwrap() as u32;
+            let (note, previous) = err.data.note().expect("should know the previous location");
+            assert_eq!(note, format!("'{}' first specified here", spec));
+            assert_eq!(previous.span, (first..first + spec.len() as u32).into());
+        }
+        // `long long` is a different type
+        assert!(decl("long long i;").is_ok());
+    }
+    #[test]
+    fn test_attributes() {
+        assert_display("unsigned __attribute__((x)) int a;", "unsigned int a;");
+        assert_display("__attribute__((aligned(8), packed)) int a;", "int a;");
+        assert_display(
+            "const [[deprecated(\"x\")]] int a;",
+            "[[deprecated(\"x\")]] const int a;",
+        );
+        assert_display("int __attribute__((unused)) a;", "int a;");
+
+        let mut p = parser("unsigned __attribute__((x)) int a;");
+        assert!(p.external_declaration().is_ok());
+        assert!(p.error_handler.pop_front().is_none());
+        let warnings: Vec<_> = p.warnings().into_iter().map(|w| w.data).collect();
+        assert_eq!(warnings, vec![Warning::IgnoredAttribute]);
+
+        assert!(decl("int __attribute__ x;").is_err());
+        assert!(decl("int __attribute__((x)").is_err());
+    }
+    #[test]
+    fn test_standard_attributes() {
+        let attributes = |s| match decl(s).unwrap().data {
+            ExternalDeclaration::Declaration(decl) => decl.attributes,
+            ExternalDeclaration::Function(func) => func.attributes,
+        };
+        let attrs = |s| -> Vec<_> { attributes(s).into_iter().map(|a| a.data).collect() };
+        assert_eq!(
+            attrs("[[noreturn]] void f(void);"),
+            vec![Attribute::NoReturn]
+        );
+        assert_eq!(
+            attrs("[[deprecated(\"use g\"), maybe_unused]] int x;"),
+            vec![
+                Attribute::Deprecated(Some("use g".into())),
+                Attribute::MaybeUnused
+            ]
+        );
+        assert_eq!(
+            attrs("[[__nodiscard__]] [[deprecated]] int f(void) { return 0; }"),
+            vec![Attribute::NoDiscard(None), Attribute::Deprecated(None)]
+        );
+        assert_eq!(attrs("[[]] int x;"), vec![]);
+        assert_no_change("[[nodiscard(\"why\")]] int f();");
+
+        // non-standard attributes are ignored
+        let mut p = parser("[[gnu::unused, unknown(1, 2)]] int x;");
+        assert!(p.external_declaration().is_ok());
+        assert!(p.error_handler.pop_front().is_none());
+        let warnings: Vec<_> = p.warnings().into_iter().map(|w| w.data).collect();
+        assert_eq!(
+            warnings,
+            vec![Warning::IgnoredAttribute, Warning::IgnoredAttribute]
+        );
+
+        // misplaced attributes
+        let misplaced = |s| match decl(s).unwrap_err().data {
+            Error::Syntax(SyntaxError::MisplacedAttribute(attr, _)) => attr,
+            other => panic!("expected misplaced attribute, got {}", other),
+        };
+        assert_eq!(misplaced("[[noreturn]] int x;"), Attribute::NoReturn);
+        assert_eq!(misplaced("[[noreturn]] int (*f)();"), Attribute::NoReturn);
+        assert_eq!(misplaced("[[fallthrough]] int x;"), Attribute::Fallthrough);
+        assert_eq!(
+            misplaced("void f([[noreturn]] int x);"),
+            Attribute::NoReturn
+        );
+        assert!(decl("[[deprecated(1)]] int x;").is_err());
+        assert!(decl("[[noreturn int x;").is_err());
+    }
+    #[test]
+    fn test_function_definition_in_struct() {
+        let mut p = parser("struct s { int f() {} }; int i;");
+        assert!(p.external_declaration().is_ok());
+        let err = p.error_handler.pop_front().unwrap();
+        assert_eq!(
+            err.data,
+            Error::Syntax(SyntaxError::FunctionDefinitionNotAllowed)
+        );
+        // no cascading errors
+        assert!(p.error_handler.pop_front().is_none());
+        assert!(p.external_declaration().is_ok());
+    }
+    #[test]
+    fn test_function_definition_in_declarator_list() {
+        let mut p = parser("int x, f() { return 0; } int i;");
+        let decl = p.external_declaration().unwrap();
+        match decl.data {
+            ExternalDeclaration::Declaration(decl) => assert_eq!(decl.declarators.len(), 2),
+            _ => panic!("expected a declaration"),
+        }
+        let err = p.error_handler.pop_front().unwrap();
+        assert_eq!(
+            err.data,
+            Error::Syntax(SyntaxError::FunctionDefinitionNotAllowed)
+        );
+        // no cascading errors
+        assert!(p.error_handler.pop_front().is_none());
+        assert!(p.external_declaration().is_ok());
+        assert!(p.is_empty());
+
+        // only a function declarator can have a body
+        let errs: Vec<_> = parser("int x, y { return 0; }")
+            .filter_map(Result::err)
+            .map(|err| err.data)
+            .collect();
+        assert!(!errs.contains(&Error::Syntax(SyntaxError::FunctionDefinitionNotAllowed)));
+        assert_eq!(errs[0].to_string(), "invalid syntax: expected ',', got '{'");
+    }
+    #[test]
+    fn test_function_returning_array() {
+        for input in &[
+            "int foo(void)[static 10];",
+            "int (foo(void))[10];",
+            "int f()[];",
+        ] {
+            let mut p = parser(input);
+            assert!(p.external_declaration().is_ok(), "{}", input);
+            let err = p.error_handler.pop_front().unwrap();
+            assert_eq!(err.data, Error::Syntax(SyntaxError::FunctionReturningArray));
+            // no cascading errors
+            assert!(p.error_handler.pop_front().is_none(), "{}", input);
+            assert!(p.is_empty());
+        }
+        // the array is never closed
+        let mut p = parser("int f()[");
+        assert!(p.external_declaration().is_err());
+        let errs: Vec<_> = std::iter::from_fn(|| p.error_handler.pop_front())
+            .map(|err| err.data)
+            .collect();
+        assert_eq!(
+            errs,
+            vec![
+                Error::Syntax(SyntaxError::EndOfFile("']'")),
+                Error::Syntax(SyntaxError::FunctionReturningArray)
+            ]
+        );
+        // returning a pointer to an array is fine
+        let mut p = parser("int (*f(void))[10];");
+        assert!(p.external_declaration().is_ok());
+        assert!(p.error_handler.pop_front().is_none());
+    }
+    #[test]
     fn test_cursed_function_declarator() {
         let decl = parser("f(())")
             .declarator(false)
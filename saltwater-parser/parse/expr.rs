@@ -2,8 +2,10 @@ use std::convert::TryFrom;
 
 use super::*;
 use crate::data::ast::{Expr, ExprType, TypeName};
-use crate::data::lex::{AssignmentToken, Keyword};
+use crate::data::error::Warning;
+use crate::data::lex::{AssignmentToken, ComparisonToken, Keyword, LiteralToken};
 use crate::data::*;
+use crate::lex::parse_float_raw;
 
 trait UnaryExprFn: FnOnce(Expr) -> ExprType {}
 impl<T: FnOnce(Expr) -> ExprType> UnaryExprFn for T {}
@@ -53,7 +55,6 @@ impl BinaryPrecedence {
         }
     }
     fn constructor(self) -> impl Fn(Expr, Expr) -> ExprType {
-        use crate::data::lex::ComparisonToken;
         use BinaryPrecedence::*;
         use ExprType::*;
         let func: Box<dyn Fn(_, _) -> _> = match self {
@@ -141,11 +142,28 @@ impl<I: Lexer> Parser<I> {
             .peek_token()
             .and_then(|tok| BinaryPrecedence::try_from(tok).ok())
         {
+            // `a =< b` is almost certainly a typo for `a <= b`
+            let transposed = self.transposed_comparison();
+            let binop = transposed.map_or(binop, |(_, intended)| intended);
             let prec = binop.prec();
             if prec < max_precedence {
                 break;
             }
-            self.next_token();
+            let op = self.next_token().unwrap();
+            if let Some((typed, _)) = transposed {
+                let second = self.next_token().unwrap();
+                self.error_handler.error(
+                    SyntaxError::TransposedOperator(typed),
+                    op.location.merge(second.location),
+                );
+            } else if op.data == Token::EQUAL && self.peek_token() == Some(&Token::LogicalNot) {
+                // `a =! b` is valid, but probably not what was meant
+                let not = self.next_location();
+                if op.location.span.end == not.span.start {
+                    self.error_handler
+                        .warn(Warning::AssignNegation, op.location.merge(not));
+                }
+            }
             let location = left.location;
             let right = if binop.left_associative() {
                 let inner_left = self.unary_expr()?;
@@ -176,6 +194,22 @@ impl<I: Lexer> Parser<I> {
         }
         Ok(left)
     }
+    // if the next two tokens are `=<` or `=>`, return the comparison that was typed
+    // and the operator that was probably intended
+    fn transposed_comparison(&mut self) -> Option<(ComparisonToken, BinaryPrecedence)> {
+        if self.peek_token() != Some(&Token::EQUAL) {
+            return None;
+        }
+        match self.peek_next_token() {
+            Some(Token::Comparison(ComparisonToken::Less)) => {
+                Some((ComparisonToken::Less, BinaryPrecedence::LessEq))
+            }
+            Some(Token::Comparison(ComparisonToken::Greater)) => {
+                Some((ComparisonToken::Greater, BinaryPrecedence::GreaterEq))
+            }
+            _ => None,
+        }
+    }
     // ambiguity between '(' expr ')' and '(' type_name ')'
     // NOTE: there is no distinction between EOF and a non-parenthesized type here
     fn parenthesized_type(&mut self) -> SyntaxResult<Option<Locatable<TypeName>>> {
@@ -207,7 +241,8 @@ impl<I: Lexer> Parser<I> {
             {
                 prefixes.push((constructor, location));
             // these keywords can be followed by either a type name or an expression
-            } else if let Some(keyword) = self.match_keywords(&[Keyword::Sizeof, Keyword::Alignof])
+            } else if let Some(keyword) =
+                self.match_keywords(&[Keyword::Sizeof, Keyword::Alignof, Keyword::GnuAlignof])
             {
                 // `sizeof(int)` is a primary expr
                 if let Some(mut ctype) = self.parenthesized_type()? {
@@ -224,6 +259,11 @@ impl<I: Lexer> Parser<I> {
                     let constructor = if keyword.data == Keyword::Sizeof {
                         ExprType::SizeofExpr
                     } else {
+                        // ISO C only allows `_Alignof(type-name)`
+                        if keyword.data == Keyword::Alignof {
+                            self.error_handler
+                                .error(SyntaxError::AlignofExpr, keyword.location);
+                        }
                         ExprType::AlignofExpr
                     };
                     prefixes.push((
@@ -260,17 +300,94 @@ impl<I: Lexer> Parser<I> {
         } else if let Some(loc) = self.match_id() {
             loc.map(ExprType::Id)
         } else if let Some(literal) = self.match_literal() {
-            let loc = literal.location;
-            match literal.data.parse() {
-                Ok(literal) => loc.with(literal).map(ExprType::Literal),
-                Err(err) => return Err(loc.with(err)),
-            }
+            literal.and_then(|lit| match lit {
+                LiteralToken::Imaginary(spelling) => {
+                    parse_float_raw(spelling.as_str()).map(ExprType::Imaginary)
+                }
+                lit => lit.parse().map(ExprType::Literal),
+            })?
+        } else if let Some(builtin) = self.match_keywords(&[Keyword::TypesCompatible]) {
+            self.types_compatible(builtin.location)?
+        } else if let Some(builtin) = self.match_keywords(&[Keyword::ChooseExpr]) {
+            self.choose_expr(builtin.location)?
+        } else if let Some(keyword) = self.match_keywords(&[Keyword::Generic]) {
+            self.generic_selection(keyword.location)?
         } else {
             return Err(self.next_location().with(SyntaxError::MissingPrimary));
         };
         self.postfix_expr(primary)
     }
 
+    /// `__builtin_types_compatible_p(int, unsigned)`
+    ///
+    /// This is kept out of `primary_expr` so the type names don't take up stack space
+    /// for every level of a deeply nested expression.
+    fn types_compatible(&mut self, start: Location) -> SyntaxResult<Expr> {
+        self.expect(Token::LeftParen)?;
+        let left = self.type_name()?;
+        self.expect(Token::Comma)?;
+        let right = self.type_name()?;
+        let end = self.expect(Token::RightParen)?.location;
+        let types = ExprType::TypesCompatible(Box::new(left.data), Box::new(right.data));
+        Ok(start.merge(end).with(types))
+    }
+
+    /// `__builtin_choose_expr(1, a, b)`
+    fn choose_expr(&mut self, start: Location) -> SyntaxResult<Expr> {
+        self.expect(Token::LeftParen)?;
+        let condition = self.assignment_expr()?;
+        self.expect(Token::Comma)?;
+        let then = self.assignment_expr()?;
+        self.expect(Token::Comma)?;
+        let otherwise = self.assignment_expr()?;
+        let end = self.expect(Token::RightParen)?.location;
+        let choose = ExprType::ChooseExpr(Box::new(condition), Box::new(then), Box::new(otherwise));
+        Ok(start.merge(end).with(choose))
+    }
+
+    /// ```yacc
+    /// generic_selection: GENERIC '(' assignment_expr ',' generic_assoc_list ')' ;
+    ///
+    /// generic_assoc_list
+    /// : generic_association
+    /// | generic_assoc_list ',' generic_association
+    /// ;
+    ///
+    /// generic_association
+    /// : type_name ':' assignment_expr
+    /// | DEFAULT ':' assignment_expr
+    /// ;
+    /// ```
+    ///
+    /// The associations are kept in order, so the analyzer can decide which one to use.
+    fn generic_selection(&mut self, start: Location) -> SyntaxResult<Expr> {
+        use crate::data::ast::GenericAssociation;
+
+        self.expect(Token::LeftParen)?;
+        let controlling = self.assignment_expr()?;
+        let mut associations = Vec::new();
+        // `_Generic(x)` or `_Generic(x,)`
+        if self.match_next(&Token::Comma).is_none() || self.peek_token() == Some(&Token::RightParen)
+        {
+            return Err(self.next_location().with(SyntaxError::EmptyGeneric));
+        }
+        loop {
+            let type_name = if self.match_keywords(&[Keyword::Default]).is_some() {
+                None
+            } else {
+                Some(self.type_name()?.data)
+            };
+            self.expect(Token::Colon)?;
+            let expr = self.assignment_expr()?;
+            associations.push(GenericAssociation { type_name, expr });
+            if self.match_next(&Token::Comma).is_none() {
+                break;
+            }
+        }
+        let end = self.expect(Token::RightParen)?.location;
+        let generic = ExprType::Generic(Box::new(controlling), associations);
+        Ok(start.merge(end).with(generic))
+    }
     // `expr` should be a primary expression
     fn postfix_expr(&mut self, mut expr: Expr) -> SyntaxResult<Expr> {
         // fortunately, all postfix expressions have the same precedence
@@ -357,6 +474,13 @@ impl<I: Lexer> Parser<I> {
                         args.push(arg);
                         if let Some(token) = self.match_next(&Token::Comma) {
                             start.merge(token.location);
+                            // f(1,)
+                            if let Some(paren) = self.match_next(&Token::RightParen) {
+                                self.error_handler
+                                    .error(SyntaxError::TrailingCommaInCall, token.location);
+                                start = start.merge(paren.location);
+                                break;
+                            }
                         } else {
                             let token = self.expect(Token::RightParen)?;
                             start = start.merge(token.location);
@@ -395,13 +519,19 @@ pub(crate) mod test {
     pub(crate) fn expr(e: &str) -> SyntaxResult<Expr> {
         parser(e).expr()
     }
+    /// An expression with a default location, for comparing with a parsed expression.
+    fn boxed(expr: ExprType) -> Box<Expr> {
+        Box::new(Location::default().with(expr))
+    }
+    fn id(name: &str) -> Box<Expr> {
+        boxed(ExprType::Id(name.into()))
+    }
 
     #[test]
     fn parse_prefix() {
         let expr_data = |s| expr(s).unwrap().data;
-        let x = || Box::new(Location::default().with(ExprType::Id("x".into())));
         fn int() -> Box<Expr> {
-            Box::new(Location::default().with(ExprType::Literal(LiteralValue::Int(1))))
+            boxed(ExprType::Literal(LiteralValue::Int(1)))
         }
         fn assert_unary_int(s: &str, c: impl Fn(Box<Expr>) -> ExprType) {
             assert_eq!(expr(s).unwrap().data, c(int()));
@@ -415,15 +545,15 @@ pub(crate) mod test {
         assert_unary_int("!1", ExprType::LogicalNot);
         assert_unary_int("&1", ExprType::AddressOf);
 
-        assert_eq!(expr_data("x"), x().data);
-        assert_eq!(expr_data("x"), x().data);
-        assert_eq!(expr_data("(((((x)))))"), x().data);
-        assert_eq!(expr_data("+(x)"), ExprType::UnaryPlus(x()));
-        assert_eq!(expr_data("-((x))"), ExprType::Negate(x()));
-        assert_eq!(expr_data("*x"), ExprType::Deref(x()));
-        assert_eq!(expr_data("~x"), ExprType::BitwiseNot(x()));
-        assert_eq!(expr_data("!x"), ExprType::LogicalNot(x()));
-        assert_eq!(expr_data("&x"), ExprType::AddressOf(x()));
+        assert_eq!(expr_data("x"), id("x").data);
+        assert_eq!(expr_data("x"), id("x").data);
+        assert_eq!(expr_data("(((((x)))))"), id("x").data);
+        assert_eq!(expr_data("+(x)"), ExprType::UnaryPlus(id("x")));
+        assert_eq!(expr_data("-((x))"), ExprType::Negate(id("x")));
+        assert_eq!(expr_data("*x"), ExprType::Deref(id("x")));
+        assert_eq!(expr_data("~x"), ExprType::BitwiseNot(id("x")));
+        assert_eq!(expr_data("!x"), ExprType::LogicalNot(id("x")));
+        assert_eq!(expr_data("&x"), ExprType::AddressOf(id("x")));
 
         assert_same("++A[1]", "++(A[1])");
         assert_same("A[1] += 1", "(A[1]) += 1");
@@ -440,6 +570,18 @@ pub(crate) mod test {
         assert_expr_display("1()()()", "(((1)())())()");
     }
     #[test]
+    fn parse_double_subscript() {
+        // a[i][j] is (a[i])[j]
+        let inner = boxed(ExprType::Index(id("a"), id("i")));
+        assert_eq!(
+            expr("a[i][j]").unwrap().data,
+            ExprType::Index(inner, id("j"))
+        );
+        assert_expr_display("a[i][j]", "((a)[i])[j]");
+        // the index is a full expression
+        assert_expr_display("a[i, j][k = 1]", "((a)[i, j])[(k) = (1)]");
+    }
+    #[test]
     fn parse_binary() {
         assert_eq!(
             expr("1 = 2 = 3 + 4*5 + 6 + 7").unwrap().to_string(),
@@ -447,9 +589,187 @@ pub(crate) mod test {
         );
     }
     #[test]
+    fn parse_comma_operator() {
+        assert_eq!(
+            expr("(a, b)").unwrap().data,
+            ExprType::Comma(id("a"), id("b"))
+        );
+        // comma is the loosest operator, and associates left-to-right
+        assert_expr_display("a = 1, b ? c : d", "(a) = (1), (b) ? (c) : (d)");
+        let left = boxed(ExprType::Comma(id("a"), id("b")));
+        assert_eq!(
+            expr("a, b, c").unwrap().data,
+            ExprType::Comma(left, id("c"))
+        );
+
+        // in an argument list, a comma separates arguments unless it's parenthesized
+        let args = |s| match expr(s).unwrap().data {
+            ExprType::FuncCall(_, args) => args.into_iter().map(|arg| arg.data).collect::<Vec<_>>(),
+            other => panic!("expected a function call, got {:?}", other),
+        };
+        assert_eq!(
+            args("f(a, b)"),
+            vec![ExprType::Id("a".into()), ExprType::Id("b".into())]
+        );
+        assert_eq!(args("f((a, b))"), vec![ExprType::Comma(id("a"), id("b"))]);
+        assert_eq!(args("f((a, b), c)").len(), 2);
+    }
+    #[test]
+    fn comma_in_subscript() {
+        use crate::data::ast::ExprType;
+
+        let index = match expr("a[b, c]").unwrap().data {
+            ExprType::Index(_, index) => index,
+            other => panic!("expected an index, got {:?}", other),
+        };
+        match index.data {
+            ExprType::Comma(left, right) => {
+                assert_eq!(left.to_string(), "b");
+                assert_eq!(right.to_string(), "c");
+            }
+            other => panic!("expected a comma expression, got {:?}", other),
+        }
+    }
+    #[test]
+    fn parse_comparisons() {
+        use crate::data::lex::ComparisonToken;
+
+        let compare = |left, right, token| boxed(ExprType::Compare(left, right, token));
+        assert_eq!(
+            expr("a < b").unwrap().data,
+            compare(id("a"), id("b"), ComparisonToken::Less).data
+        );
+        for op in &["<", ">", "<=", ">=", "==", "!="] {
+            assert_expr_display(&format!("a {} b", op), &format!("(a) {} (b)", op));
+        }
+        // relational operators bind more tightly than equality operators
+        let expected = compare(
+            compare(id("a"), id("b"), ComparisonToken::Less),
+            compare(id("c"), id("d"), ComparisonToken::Less),
+            ComparisonToken::EqualEqual,
+        );
+        assert_eq!(expr("a < b == c < d").unwrap().data, expected.data);
+        assert_expr_display("a != b >= c", "(a) != ((b) >= (c))");
+        // both associate left-to-right
+        assert_expr_display("a < b > c", "((a) < (b)) > (c)");
+        assert_expr_display("a == b != c", "((a) == (b)) != (c)");
+        // and bind more loosely than shifts
+        assert_expr_display("a << 1 < b", "((a) << (1)) < (b)");
+    }
+    #[test]
+    fn transposed_comparison() {
+        use crate::data::error::Warning;
+        use crate::data::lex::ComparisonToken;
+
+        let mut p = parser("a =< b");
+        assert_eq!(p.expr().unwrap().to_string(), "(a) <= (b)");
+        let err = p.error_handler.pop_front().unwrap().data;
+        assert_eq!(
+            err,
+            Error::Syntax(SyntaxError::TransposedOperator(ComparisonToken::Less))
+        );
+        assert!(err.to_string().contains("did you mean '<='?"));
+        assert_expr_display("a => b", "(a) >= (b)");
+        assert_expr_display("a = b =< c", "(a) = ((b) <= (c))");
+
+        // `=!` is valid C, so only warn
+        let mut p = parser("a =! b");
+        assert_eq!(p.expr(), expr("a = !b"));
+        assert!(p.error_handler.pop_front().is_none());
+        assert_eq!(
+            p.warnings().pop_front().unwrap().data,
+            Warning::AssignNegation
+        );
+        let mut p = parser("a = !b");
+        p.expr().unwrap();
+        assert!(p.warnings().is_empty());
+    }
+    #[test]
     fn parse_ternary() {
         assert_expr_display("1||2 ? 3||4 : 5", "((1) || (2)) ? ((3) || (4)) : (5)");
         assert_expr_display("1||2 ? 3?4:5 : 6", "((1) || (2)) ? ((3) ? (4) : (5)) : (6)");
+        assert_expr_display("a ? b : c", "(a) ? (b) : (c)");
+        // nested conditionals associate right-to-left
+        assert_same("a ? b ? c : d : e", "a ? (b ? c : d) : e");
+        assert_same("a ? b : c ? d : e", "a ? b : (c ? d : e)");
+        assert_expr_display("a ? b : c ? d : e", "(a) ? (b) : ((c) ? (d) : (e))");
+        // lower precedence than `||`, higher than assignment
+        assert_same("a || b ? c : d", "(a || b) ? c : d");
+        assert_same("a = b ? c : d", "a = (b ? c : d)");
+    }
+    #[test]
+    fn parse_assignment() {
+        use crate::data::lex::AssignmentToken;
+
+        // assignment associates right-to-left
+        assert_expr_display("a = b = c", "(a) = ((b) = (c))");
+        let inner = boxed(ExprType::Assign(id("b"), id("c"), AssignmentToken::Equal));
+        assert_eq!(
+            expr("a = b = c").unwrap().data,
+            ExprType::Assign(id("a"), inner, AssignmentToken::Equal)
+        );
+        for op in &["+=", "-=", "*=", "/=", "%=", "&=", "|=", "^=", "<<=", ">>="] {
+            assert_expr_display(
+                &format!("a {} b {} 1", op, op),
+                &format!("(a) {} ((b) {} (1))", op, op),
+            );
+        }
+        // below the conditional operator, above the comma operator
+        assert_expr_display("a = b || c", "(a) = ((b) || (c))");
+        assert_expr_display("a = 1, b = 2", "(a) = (1), (b) = (2)");
+    }
+    #[test]
+    fn parse_c23_alignof() {
+        use crate::lex::PreProcessorBuilder;
+        use crate::parse::Parser;
+
+        let c23 = |s: &str| {
+            let cpp = PreProcessorBuilder::new(format!("{}\n", s))
+                .c23(true)
+                .build();
+            Parser::new(cpp, false).expr().unwrap().to_string()
+        };
+        assert_eq!(c23("alignof(int)"), "alignof(int)");
+        assert_eq!(c23("_Alignof(int)"), "alignof(int)");
+        // before C23, `alignof` is an ordinary identifier
+        assert_expr_display("alignof(x)", "(alignof)(x)");
+    }
+    #[test]
+    fn parse_logical_operators() {
+        let and = boxed(ExprType::LogicalAnd(id("b"), id("c")));
+        assert_eq!(
+            expr("a || b && c").unwrap().data,
+            ExprType::LogicalOr(id("a"), and)
+        );
+        assert_expr_display("a && b || c", "((a) && (b)) || (c)");
+        // both associate left-to-right
+        assert_expr_display("a || b || c", "((a) || (b)) || (c)");
+        assert_expr_display("a && b && c", "((a) && (b)) && (c)");
+        // and both bind more loosely than comparisons
+        assert_expr_display("a < b && c == d", "((a) < (b)) && ((c) == (d))");
+        assert_expr_display("a | b || c & d", "((a) | (b)) || ((c) & (d))");
+    }
+    #[test]
+    fn parse_call_arguments() {
+        let args = |s| match expr(s).unwrap().data {
+            ExprType::FuncCall(func, args) => {
+                assert_eq!(func.data, ExprType::Id("f".into()));
+                args.into_iter().map(|arg| arg.data).collect::<Vec<_>>()
+            }
+            other => panic!("expected a function call, got {:?}", other),
+        };
+        let int = |i| ExprType::Literal(LiteralValue::Int(i));
+        assert!(args("f()").is_empty());
+        assert_eq!(args("f(1)"), vec![int(1)]);
+        assert_eq!(args("f(1, 2, 3)"), vec![int(1), int(2), int(3)]);
+
+        let mut p = parser("f(1, 2,) + 1");
+        let call = p.expr().unwrap();
+        assert_eq!(call.to_string(), "((f)(1, 2)) + (1)");
+        let err = p.error_handler.pop_front().unwrap();
+        assert_eq!(err.data, SyntaxError::TrailingCommaInCall.into());
+        assert_eq!(err.location.span, (6..7).into());
+        assert!(p.error_handler.pop_front().is_none());
     }
     #[test]
     fn parse_casts() {
@@ -461,10 +781,223 @@ pub(crate) mod test {
         assert_expr_display("sizeof 1 + 2", "(sizeof(1)) + (2)");
         // sizeof(int) takes precedence over (int)1
         assert_expr_display("sizeof (int)1 + 2", "sizeof(int)");
+
+        let type_name = |s| parser(s).type_name().unwrap().data;
+        assert_eq!(
+            expr("(int)x").unwrap().data,
+            ExprType::Cast(type_name("int"), id("x"))
+        );
+        let zero = boxed(ExprType::Literal(LiteralValue::Int(0)));
+        assert_eq!(
+            expr("(char*)0").unwrap().data,
+            ExprType::Cast(type_name("char*"), zero)
+        );
+        // without a type, parentheses only group
+        assert_eq!(expr("(x)").unwrap().data, id("x").data);
+        assert_expr_display("(x)+1", "(x) + (1)");
+        assert_expr_display("(int)+1", "(int)(+(1))");
+        // typedef names are types
+        let mut p = parser("typedef int T; (T)-x");
+        p.external_declaration().unwrap();
+        assert_eq!(p.expr().unwrap().to_string(), "(T)(-(x))");
+    }
+    #[test]
+    fn parse_function_pointer_cast() {
+        let type_name = |s| parser(s).type_name().unwrap().data;
+        match expr("(int(*)[3])q").unwrap().data {
+            ExprType::Cast(ctype, inner) => {
+                assert_eq!(ctype, type_name("int(*)[3]"));
+                assert_eq!(inner.data, ExprType::Id("q".into()));
+            }
+            other => panic!("expected a cast, got {:?}", other),
+        }
+        match expr("((void(*)(void))p)()").unwrap().data {
+            ExprType::FuncCall(func, args) => {
+                assert!(args.is_empty());
+                match func.data {
+                    ExprType::Cast(ctype, inner) => {
+                        assert_eq!(ctype, type_name("void(*)(void)"));
+                        assert_eq!(inner.data, ExprType::Id("p".into()));
+                    }
+                    other => panic!("expected a cast, got {:?}", other),
+                }
+            }
+            other => panic!("expected a function call, got {:?}", other),
+        }
+    }
+    #[test]
+    fn types_compatible() {
+        assert_expr_display(
+            "__builtin_types_compatible_p(int, unsigned int)",
+            "__builtin_types_compatible_p(int, unsigned int)",
+        );
+        assert_same(
+            "__builtin_types_compatible_p(int *, int[])",
+            "__builtin_types_compatible_p(int*,int [ ])",
+        );
+        assert_eq!(
+            expr("__builtin_types_compatible_p(1, int)")
+                .unwrap_err()
+                .data,
+            SyntaxError::ExpectedType
+        );
+    }
+    #[test]
+    fn choose_expr() {
+        assert_expr_display(
+            "__builtin_choose_expr(1, a, b + c)",
+            "__builtin_choose_expr(1, a, (b) + (c))",
+        );
+        // the comma operator is not allowed in the arguments
+        assert!(expr("__builtin_choose_expr(1, a, b, c)").is_err());
+    }
+    #[test]
+    fn parse_float_literals() {
+        let float = |f| ExprType::Literal(LiteralValue::Float(f));
+        assert_eq!(expr("2.75").unwrap().data, float(2.75));
+        assert_eq!(expr("1.5f").unwrap().data, float(1.5));
+        assert_eq!(expr(".5").unwrap().data, float(0.5));
+        assert_eq!(expr("1e-2").unwrap().data, float(0.01));
+        assert_eq!(expr("0x1p4").unwrap().data, float(16.0));
+        // integers stay integers
+        assert_eq!(
+            expr("3").unwrap().data,
+            ExprType::Literal(LiteralValue::Int(3))
+        );
+        assert_eq!(
+            expr("2.5 * 2").unwrap().data,
+            ExprType::Mul(
+                boxed(float(2.5)),
+                boxed(ExprType::Literal(LiteralValue::Int(2)))
+            )
+        );
+    }
+    #[test]
+    fn parse_increments() {
+        let data = |s| expr(s).unwrap().data;
+        assert_eq!(data("++a"), ExprType::PreIncrement(id("a"), true));
+        assert_eq!(data("--a"), ExprType::PreIncrement(id("a"), false));
+        assert_eq!(data("a++"), ExprType::PostIncrement(id("a"), true));
+        assert_eq!(data("a--"), ExprType::PostIncrement(id("a"), false));
+        // two minus signs separated by whitespace are two negations
+        let negate = boxed(ExprType::Negate(id("a")));
+        assert_eq!(data("- -a"), ExprType::Negate(negate));
+        // postfix binds more tightly than prefix
+        assert_expr_display("++a--", "++((a)--)");
+        assert_expr_display("-a++", "-((a)++)");
+    }
+    #[test]
+    fn maximal_munch() {
+        // the lexer always takes the longest token, so this is `a -- - b`
+        let mut p = parser("a---b");
+        let tokens: Vec<_> = std::iter::from_fn(|| p.next_token())
+            .map(|token| token.data)
+            .collect();
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Id("a".into()),
+                Token::MinusMinus,
+                Token::Minus,
+                Token::Id("b".into())
+            ]
+        );
+        assert_expr_display("a---b", "((a)--) - (b)");
+        assert_expr_display("a- --b", "(a) - (--(b))");
+        assert_expr_display("a-- -b", "((a)--) - (b)");
+        assert_expr_display("a - - -b", "(a) - (-(-(b)))");
+        assert_expr_display("a+++b", "((a)++) + (b)");
+        // `a -- -- - b`, which is syntactically valid even though `a--` isn't an lvalue
+        assert_expr_display("a-----b", "(((a)--)--) - (b)");
     }
     #[test]
     fn sizeof() {
         assert_same("sizeof(int)++", "(sizeof(int))++");
         assert_same("++sizeof(int)", "++(sizeof(int))");
+
+        let type_name = |s| parser(s).type_name().unwrap().data;
+        assert_eq!(
+            expr("sizeof x").unwrap().data,
+            ExprType::SizeofExpr(id("x"))
+        );
+        assert_eq!(
+            expr("sizeof(x)").unwrap().data,
+            ExprType::SizeofExpr(id("x"))
+        );
+        assert_eq!(
+            expr("sizeof(int)").unwrap().data,
+            ExprType::SizeofType(type_name("int"))
+        );
+        assert_eq!(
+            expr("sizeof(int*)").unwrap().data,
+            ExprType::SizeofType(type_name("int*"))
+        );
+        // typedef names are types, not expressions
+        let mut p = parser("typedef int T; sizeof(T)");
+        p.external_declaration().unwrap();
+        match p.expr().unwrap().data {
+            ExprType::SizeofType(_) => {}
+            other => panic!("expected sizeof a type, got {:?}", other),
+        }
+        // member access is part of the operand, with or without parentheses
+        let member = boxed(ExprType::Member(id("s"), "x".into()));
+        let expected = ExprType::SizeofExpr(member);
+        assert_eq!(expr("sizeof s.x").unwrap().data, expected);
+        assert_eq!(expr("sizeof(s.x)").unwrap().data, expected);
+        assert_expr_display("sizeof p->x", "sizeof((p)->x)");
+    }
+    #[test]
+    fn alignof() {
+        use crate::data::error::SyntaxError;
+
+        assert_expr_display("_Alignof(int)", "alignof(int)");
+        assert_expr_display("_Alignof(double)", "alignof(double)");
+        assert_expr_display("__alignof__(int)", "alignof(int)");
+        assert_expr_display("__alignof(int)", "alignof(int)");
+        let mut p = parser("_Alignof(int)");
+        p.expr().unwrap();
+        assert!(p.warnings().is_empty());
+
+        // only allowed on expressions as an extension
+        for iso in &["_Alignof x", "_Alignof +1"] {
+            let mut p = parser(iso);
+            p.expr().unwrap();
+            let err = p.error_handler.pop_front().unwrap().data;
+            assert_eq!(err, SyntaxError::AlignofExpr.into());
+        }
+        let mut p = parser("__alignof__ x");
+        assert_eq!(p.expr().unwrap().to_string(), "alignof(x)");
+        assert!(p.warnings().is_empty());
+        assert_expr_display("__alignof x", "alignof(x)");
+    }
+    #[test]
+    fn generic_selection() {
+        use crate::data::error::SyntaxError;
+
+        assert_expr_display(
+            "_Generic(x, int: 1, default: 0)",
+            "_Generic(x, int: 1, default: 0)",
+        );
+        // `default` can come anywhere, and the order is kept
+        let e = expr("_Generic(x, default: 0, int: 1, double: 2)").unwrap();
+        match e.data {
+            ExprType::Generic(_, associations) => {
+                let types: Vec<_> = associations
+                    .iter()
+                    .map(|assoc| assoc.type_name.as_ref().map(ToString::to_string))
+                    .collect();
+                assert_eq!(types, vec![None, Some("int".into()), Some("double".into())]);
+            }
+            other => panic!("expected _Generic, got {:?}", other),
+        }
+        assert_eq!(
+            expr("_Generic(x)").unwrap_err().data,
+            SyntaxError::EmptyGeneric
+        );
+        assert_eq!(
+            expr("_Generic(x,)").unwrap_err().data,
+            SyntaxError::EmptyGeneric
+        );
+        assert!(expr("_Generic(x, int 1)").is_err());
     }
 }
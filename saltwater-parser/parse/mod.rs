@@ -2,12 +2,18 @@ pub(crate) mod decl;
 mod expr;
 mod stmt;
 
-use std::collections::VecDeque;
+use std::collections::{HashSet, VecDeque};
 use std::iter::Iterator;
 use std::mem;
+use std::rc::Rc;
 
 use crate::data::*;
-use crate::data::{ast::ExternalDeclaration, hir::Scope, lex::Keyword};
+use crate::data::{
+    ast::{Attribute, ExternalDeclaration},
+    error::Warning,
+    hir::Scope,
+    lex::{Keyword, TokenKind},
+};
 use crate::RecursionGuard;
 
 type Lexeme = CompileResult<Locatable<Token>>;
@@ -43,6 +49,22 @@ pub struct Parser<I: Lexer> {
     error_handler: ErrorHandler,
     /// Internal API which prevents segfaults due to stack overflow
     recursion_guard: RecursionGuard,
+    /// the last comment seen in whitespace, if the lexer preserves comments
+    pending_comment: Option<String>,
+    /// comments attached to tokens which have been lexed but not yet consumed
+    doc_comments: VecDeque<(Location, String)>,
+    /// whether to accept old-style function definitions: `int f(a) int a; {}`
+    kr_definitions: bool,
+    /// whether to warn about features that were added after C89
+    c89: bool,
+    /// whether to accept features that were added in C23 without warning
+    c23: bool,
+    /// whether to accept GNU extensions to the syntax
+    gnu_extensions: bool,
+    /// whether to warn about harmless code that ISO C doesn't allow, like `x;;` in a block
+    pedantic: bool,
+    /// standard attributes which have been parsed but not yet attached to a declaration
+    attributes: Vec<Locatable<Attribute>>,
 }
 
 impl<I: Lexer> Parser<I> {
@@ -61,8 +83,65 @@ impl<I: Lexer> Parser<I> {
             debug,
             error_handler: ErrorHandler::new(),
             recursion_guard: Default::default(),
+            pending_comment: None,
+            doc_comments: VecDeque::new(),
+            kr_definitions: false,
+            c89: false,
+            c23: false,
+            gnu_extensions: false,
+            pedantic: false,
+            attributes: Vec::new(),
         }
     }
+    /// Accept old-style (K&R) function definitions, such as `int f(a, b) int a; double b; {}`.
+    ///
+    /// Without this, they are parsed anyway but reported as an error.
+    pub fn kr_definitions(mut self, yes: bool) -> Self {
+        self.kr_definitions = yes;
+        self
+    }
+    /// Warn about code that is valid C99 but not C89, such as declarations after statements.
+    pub fn c89(mut self, yes: bool) -> Self {
+        self.c89 = yes;
+        self
+    }
+    /// Accept code that is valid C23 but not C17, such as a label at the end of a block.
+    ///
+    /// Without this, such code is still parsed, but with a warning.
+    pub fn c23(mut self, yes: bool) -> Self {
+        self.c23 = yes;
+        self
+    }
+    /// Accept GNU extensions to the syntax, such as the old-style designator `{ x: 1 }`.
+    ///
+    /// Without this, they are parsed anyway but reported as an error.
+    pub fn gnu_extensions(mut self, yes: bool) -> Self {
+        self.gnu_extensions = yes;
+        self
+    }
+    /// Warn about harmless code that ISO C doesn't allow, such as `x;;` in a block.
+    pub fn pedantic(mut self, yes: bool) -> Self {
+        self.pedantic = yes;
+        self
+    }
+    /// Treat `typedefs` as if they had been declared at the start of the file.
+    ///
+    /// This is meant to be used with `Parser::typedefs`, so that a common header
+    /// only has to be parsed once. Note that the analyzer must also know about
+    /// the typedefs, or it will not be able to analyze declarations that use them.
+    /// The string interner is global, so names can be shared between parsers freely.
+    pub fn with_typedefs(mut self, typedefs: Rc<HashSet<InternedStr>>) -> Self {
+        for &name in typedefs.iter() {
+            self.typedefs.insert(name, ());
+        }
+        self
+    }
+    /// Return the names of all typedefs in scope.
+    ///
+    /// After the parser has finished, this is every typedef declared at file scope.
+    pub fn typedefs(&self) -> Rc<HashSet<InternedStr>> {
+        Rc::new(self.typedefs.iter().map(|(&name, _)| name).collect())
+    }
     /// Return whether this parser has fully finished parsing.
     ///
     /// This can be used if, for example, you call `parser.expr()`
@@ -70,6 +149,20 @@ impl<I: Lexer> Parser<I> {
     pub fn is_empty(&mut self) -> bool {
         self.peek_token().is_none()
     }
+    /// Return the tokens this parser would see, without parsing them.
+    ///
+    /// This is useful for debugging, since whitespace has been removed
+    /// and adjacent string literals have been concatenated.
+    pub fn tokens(mut self) -> impl Iterator<Item = CompileResult<Locatable<Token>>> {
+        std::iter::from_fn(move || {
+            if let Some(err) = self.error_handler.pop_front() {
+                return Some(Err(err));
+            }
+            self.next_token()
+                .map(Ok)
+                .or_else(|| self.error_handler.pop_front().map(Err))
+        })
+    }
 }
 
 impl<I: Lexer> Iterator for Parser<I> {
@@ -114,6 +207,13 @@ impl<I: Lexer> Iterator for Parser<I> {
                 self.error_handler
                     .warn("extraneous semicolon at top level", locatable.location);
             }
+            // Pragmas outside of a function have nowhere to go
+            if let Some(Token::Pragma(_)) = self.peek_token() {
+                let pragma = self.next_token().unwrap();
+                self.error_handler
+                    .warn(Warning::IgnoredPragma, pragma.location);
+                continue;
+            }
 
             // Parse more of our file
             match self.external_declaration() {
@@ -138,12 +238,15 @@ impl<I: Lexer> Parser<I> {
     // don't use this, use next_token instead
     // WARNING: this _cannot_ read or modify `self.current` or `self.next`
     fn __impl_next_token(&mut self) -> Option<Locatable<Token>> {
-        loop {
+        let token = loop {
             match self.tokens.next() {
                 Some(Ok(Locatable {
-                    data: Token::Whitespace(_),
+                    data: Token::Whitespace(ws),
                     ..
-                })) => continue,
+                })) => {
+                    self.pending_comment = last_comment(&ws);
+                    continue;
+                }
                 Some(Ok(Locatable {
                     data: Token::Literal(LiteralToken::Str(mut concat_strs)),
                     mut location,
@@ -197,6 +300,25 @@ impl<I: Lexer> Parser<I> {
                 }
                 None => break None,
             }
+        };
+        if let (Some(token), Some(comment)) = (&token, self.pending_comment.take()) {
+            self.doc_comments.push_back((token.location, comment));
+        }
+        token
+    }
+    /// Return the comment immediately before the next token, if there is one.
+    ///
+    /// This is only ever `Some` if the lexer was told to preserve comments.
+    fn doc_comment(&mut self) -> Option<String> {
+        self.peek_token()?;
+        let current = self.current.as_ref().map(|t| t.location);
+        let next = self.next.as_ref().map(|t| t.location);
+        // discard comments for tokens that have already been consumed
+        self.doc_comments
+            .retain(|(loc, _)| Some(*loc) == current || Some(*loc) == next);
+        match self.doc_comments.front() {
+            Some((loc, _)) if Some(*loc) == current => self.doc_comments.pop_front().map(|c| c.1),
+            _ => None,
         }
     }
     fn next_token(&mut self) -> Option<Locatable<Token>> {
@@ -218,6 +340,181 @@ impl<I: Lexer> Parser<I> {
         }
         self.next.as_ref().map(|x| &x.data)
     }
+    /// Like `peek_token`, but skips over attributes.
+    ///
+    /// Standard attributes (like `[[noreturn]]`) are saved in `self.attributes`.
+    /// All others (including `__attribute__((...))`) are ignored with a warning.
+    fn peek_significant(&mut self) -> Option<&Token> {
+        while let Some(start) = self.next_location_if_attribute() {
+            if self.peek_token() == Some(&Token::LeftBracket) {
+                self.standard_attributes();
+            } else if let Some(end) = self.skip_balanced() {
+                self.error_handler
+                    .warn(Warning::IgnoredAttribute, start.merge(end));
+            }
+        }
+        self.peek_token()
+    }
+    /// ```yacc
+    /// attribute_specifier: '[' '[' attribute_list ']' ']' ;
+    ///
+    /// attribute_list
+    /// : attribute?
+    /// | attribute_list ',' attribute?
+    /// ;
+    /// ```
+    fn standard_attributes(&mut self) {
+        self.expect(Token::LeftBracket)
+            .expect("standard_attributes should only be called with '[[' as the next token");
+        self.next_token();
+        loop {
+            if self.match_next(&Token::Comma).is_some() {
+                continue;
+            }
+            // `[[]]` is allowed
+            if self.match_next(&Token::RightBracket).is_some() {
+                if let Err(err) = self.expect(Token::RightBracket) {
+                    self.error_handler.push_back(err);
+                }
+                return;
+            }
+            match self.attribute() {
+                Ok(Some(attr)) => self.attributes.push(attr),
+                Ok(None) => {}
+                Err(err) => {
+                    self.error_handler.push_back(err);
+                    // skip to the end of the attribute
+                    while let Some(token) = self.next_token() {
+                        if token.data == Token::RightBracket
+                            && self.match_next(&Token::RightBracket).is_some()
+                        {
+                            return;
+                        }
+                    }
+                    return;
+                }
+            }
+        }
+    }
+    /// ```yacc
+    /// attribute
+    /// : identifier attribute_arguments?
+    /// | identifier ':' ':' identifier attribute_arguments?
+    /// ;
+    /// ```
+    ///
+    /// Returns `None` if the attribute isn't a standard attribute.
+    fn attribute(&mut self) -> SyntaxResult<Option<Locatable<Attribute>>> {
+        let Locatable {
+            data: name,
+            mut location,
+        } = self.expect_id()?;
+        // vendor attributes, like `[[gnu::unused]]`
+        let vendor = if self.match_next(&Token::Colon).is_some() {
+            self.expect(Token::Colon)?;
+            location = location.merge(self.expect_id()?.location);
+            true
+        } else {
+            false
+        };
+        let name = name.resolve_and_clone();
+        // `[[__noreturn__]]` is the same as `[[noreturn]]`
+        let name = if name.len() > 4 && name.starts_with("__") && name.ends_with("__") {
+            &name[2..name.len() - 2]
+        } else {
+            name.as_str()
+        };
+        let attr = match name {
+            _ if vendor => None,
+            "noreturn" => Some(Attribute::NoReturn),
+            "maybe_unused" => Some(Attribute::MaybeUnused),
+            "fallthrough" => Some(Attribute::Fallthrough),
+            "deprecated" => Some(Attribute::Deprecated(self.attribute_reason()?)),
+            "nodiscard" => Some(Attribute::NoDiscard(self.attribute_reason()?)),
+            _ => None,
+        };
+        match attr {
+            Some(attr) => Ok(Some(Locatable::new(attr, location))),
+            None => {
+                if self.peek_token() == Some(&Token::LeftParen) {
+                    if let Some(end) = self.skip_balanced() {
+                        location = location.merge(end);
+                    }
+                }
+                self.error_handler.warn(Warning::IgnoredAttribute, location);
+                Ok(None)
+            }
+        }
+    }
+    /// The optional reason for `[[deprecated("reason")]]` or `[[nodiscard("reason")]]`
+    fn attribute_reason(&mut self) -> SyntaxResult<Option<String>> {
+        if self.match_next(&Token::LeftParen).is_none() {
+            return Ok(None);
+        }
+        let reason = self.string_literal()?;
+        self.expect(Token::RightParen)?;
+        Ok(Some(reason))
+    }
+    /// If the next tokens start an attribute, consume `__attribute__` (if present)
+    /// and return the location of the start of the attribute.
+    fn next_location_if_attribute(&mut self) -> Option<Location> {
+        let start = self.next_location();
+        let attribute = InternedStr::get_or_intern("__attribute__");
+        if self.peek_token() == Some(&Token::Id(attribute)) {
+            self.next_token();
+            Some(start)
+        } else if self.peek_token() == Some(&Token::LeftBracket)
+            && self.peek_next_token() == Some(&Token::LeftBracket)
+        {
+            Some(start)
+        } else {
+            None
+        }
+    }
+    /// Skip a parenthesized or bracketed group of tokens, including the delimiters.
+    ///
+    /// Returns the location of the closing delimiter,
+    /// or `None` if there was an error (which has already been reported).
+    fn skip_balanced(&mut self) -> Option<Location> {
+        // the closing delimiter for each group that's still open
+        let mut closing = Vec::new();
+        loop {
+            let token = match self.next_token() {
+                Some(token) => token,
+                None => {
+                    let err = match closing.last() {
+                        // `int f()[`
+                        Some(&expected) => SyntaxError::EndOfFile(expected),
+                        None => SyntaxError::ExpectedOneOf(
+                            vec![Token::LeftParen, Token::LeftBracket],
+                            None,
+                        ),
+                    };
+                    self.error_handler.error(err, self.last_location);
+                    return None;
+                }
+            };
+            match token.data {
+                Token::LeftParen => closing.push("')'"),
+                Token::LeftBracket => closing.push("']'"),
+                Token::RightParen | Token::RightBracket => {
+                    closing.pop();
+                }
+                _ if closing.is_empty() => {
+                    let err = SyntaxError::ExpectedOneOf(
+                        vec![Token::LeftParen, Token::LeftBracket],
+                        Some(token.data),
+                    );
+                    self.error_handler.error(err, token.location);
+                    return None;
+                }
+                _ => {}
+            }
+            if closing.is_empty() {
+                return Some(token.location);
+            }
+        }
+    }
     fn next_location(&self) -> Location {
         if let Some(token) = &self.current {
             token.location
@@ -248,16 +545,10 @@ impl<I: Lexer> Parser<I> {
         }
     }
     fn match_literal(&mut self) -> Option<Locatable<LiteralToken>> {
-        let next = self.next_token();
-        if let Some(Locatable {
-            data: Token::Literal(lit),
-            location,
-        }) = next
-        {
-            Some(location.with(lit))
-        } else {
-            self.unput(next);
-            None
+        let next = self.match_kind(TokenKind::Literal)?;
+        match next.data {
+            Token::Literal(lit) => Some(next.location.with(lit)),
+            _ => unreachable!("TokenKind::Literal should only match literals"),
         }
     }
     fn match_next(&mut self, next: &Token) -> Option<Locatable<Token>> {
@@ -273,6 +564,18 @@ impl<I: Lexer> Parser<I> {
         }
         None
     }
+    /// Consume the next token if it has the given kind, e.g. any identifier.
+    fn match_kind(&mut self, kind: TokenKind) -> Option<Locatable<Token>> {
+        self.match_any_kind(&[kind])
+    }
+    fn match_any_kind(&mut self, choices: &[TokenKind]) -> Option<Locatable<Token>> {
+        let next = self.peek_token()?.kind();
+        if choices.contains(&next) {
+            self.next_token()
+        } else {
+            None
+        }
+    }
     /*
      * If we're in an invalid state, try to recover.
      * Consume tokens until the end of a statement - either ';' or '}'
@@ -324,6 +627,27 @@ impl<I: Lexer> Parser<I> {
             err
         }
     }
+    /// Like `expect`, but accepts any one of `choices`.
+    ///
+    /// Tokens with data (like `Token::Id`) match by kind, so the data in `choices` is ignored.
+    fn expect_any(&mut self, choices: &[Token]) -> SyntaxResult<Locatable<Token>> {
+        let found = self.peek_token().cloned();
+        match &found {
+            Some(token) if choices.iter().any(|choice| token.same_kind(choice)) => {
+                Ok(self.next_token().unwrap())
+            }
+            _ => {
+                let location = if found.is_some() {
+                    self.next_location()
+                } else {
+                    self.last_location
+                };
+                let err = location.with(SyntaxError::ExpectedOneOf(choices.to_vec(), found));
+                self.panic();
+                Err(err)
+            }
+        }
+    }
     /// - replace `self.current` with `item`
     /// - replace `self.next` with `self.current`
     /// - the previous value of `self.next` is lost
@@ -345,6 +669,19 @@ impl<I: Lexer> Parser<I> {
         }
         (decls, errs)
     }
+    /// Parse the rest of the input, returning every successful declaration and every diagnostic.
+    ///
+    /// Unlike `collect_results`, this consumes the parser,
+    /// and warnings are included in the diagnostics as `Error::Warning`
+    /// instead of being left for `warnings()`.
+    pub fn into_declarations_and_diagnostics(
+        mut self,
+    ) -> (Vec<Locatable<ExternalDeclaration>>, Vec<CompileError>) {
+        let (decls, mut diagnostics) = self.collect_results();
+        let warnings = self.warnings().into_iter();
+        diagnostics.extend(warnings.map(|warning| warning.map(Error::Warning)));
+        (decls, diagnostics)
+    }
     /// Return all warnings seen so far.
     ///
     /// These warnings are consumed and will not be returned if you call
@@ -354,16 +691,32 @@ impl<I: Lexer> Parser<I> {
     }
 }
 
+/// Return the text of the last comment in a whitespace token, without the delimiters.
+fn last_comment(mut whitespace: &str) -> Option<String> {
+    let mut last = None;
+    loop {
+        whitespace = whitespace.trim_start();
+        if whitespace.starts_with("//") {
+            let end = whitespace.find('\n').unwrap_or(whitespace.len());
+            last = Some(&whitespace[2..end]);
+            whitespace = &whitespace[end..];
+        } else if whitespace.starts_with("/*") {
+            let end = whitespace[2..]
+                .find("*/")
+                .map_or(whitespace.len(), |i| i + 2);
+            last = Some(&whitespace[2..end]);
+            whitespace = whitespace.get(end + 2..).unwrap_or("");
+        } else {
+            return last.map(|comment| comment.trim().to_string());
+        }
+    }
+}
+
 impl Token {
     fn same_kind(&self, other: &Self) -> bool {
-        match (self, other) {
-            // special case keywords, assignments, and comparisons - they must match exactly
-            (Token::Keyword(left), Token::Keyword(right)) => left == right,
-            (Token::Assignment(left), Token::Assignment(right)) => left == right,
-            (Token::Comparison(left), Token::Comparison(right)) => left == right,
-            // in any other case, we're just checking they're the same enum variant
-            _ => mem::discriminant(self) == mem::discriminant(other),
-        }
+        // keywords, assignments, and comparisons must match exactly;
+        // in any other case, we're just checking they're the same enum variant
+        self.kind() == other.kind()
     }
 }
 
@@ -419,6 +772,86 @@ pub(crate) mod test {
         }
     }
 
+    #[test]
+    fn test_tokens() {
+        use crate::data::lex::Keyword;
+
+        let tokens = |s| {
+            parser(s)
+                .tokens()
+                .map(|t| t.map(|t| t.data))
+                .collect::<Result<Vec<_>, _>>()
+        };
+        assert_eq!(
+            tokens("int *p;").unwrap(),
+            vec![
+                Token::Keyword(Keyword::Int),
+                Token::Star,
+                Token::Id("p".into()),
+                Token::Semicolon
+            ]
+        );
+        assert_eq!(tokens("\"a\" \"b\"").unwrap().len(), 1);
+        assert!(tokens("int `;").is_err());
+    }
+
+    #[test]
+    fn test_shared_typedefs() {
+        let mut preamble = parser("typedef int T; typedef char *S;");
+        assert!(preamble.by_ref().all(|decl| decl.is_ok()));
+        let typedefs = preamble.typedefs();
+        assert_eq!(typedefs.len(), 2);
+
+        let decls: Vec<_> = parser("T x; S s;")
+            .with_typedefs(typedefs.clone())
+            .collect();
+        assert_eq!(decls.len(), 2);
+        assert!(decls.iter().all(|decl| decl.is_ok()));
+        // without the preamble, `T` is just an identifier
+        assert!(parser("T x;").any(|decl| decl.is_err()));
+        // the table can be reused
+        assert!(parser("T y;")
+            .with_typedefs(typedefs)
+            .all(|decl| decl.is_ok()));
+    }
+
+    #[test]
+    fn test_into_declarations_and_diagnostics() {
+        use crate::data::ast::{Declaration, ExternalDeclaration};
+        use crate::data::error::Warning;
+
+        let (decls, diagnostics) =
+            parser("int x; int 1; int; int z;").into_declarations_and_diagnostics();
+        let names: Vec<_> = decls
+            .into_iter()
+            .flat_map(|decl| match decl.data {
+                ExternalDeclaration::Declaration(Declaration { declarators, .. }) => declarators,
+                ExternalDeclaration::Function(_) => panic!("expected a declaration"),
+            })
+            .filter_map(|init| init.data.declarator.id)
+            .collect();
+        assert_eq!(names, vec![InternedStr::from("x"), InternedStr::from("z")]);
+
+        // `int 1` is an error, `int;` is a warning
+        let (warnings, errors): (Vec<_>, Vec<_>) = diagnostics
+            .into_iter()
+            .partition(|diagnostic| matches!(diagnostic.data, Error::Warning(_)));
+        assert!(!errors.is_empty());
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].data, Error::Warning(Warning::EmptyDeclaration));
+    }
+
+    #[test]
+    fn test_top_level_pragma() {
+        use crate::data::error::Warning;
+
+        let (decls, diagnostics) =
+            parser("_Pragma(\"once\") int x;").into_declarations_and_diagnostics();
+        assert_eq!(decls.len(), 1);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].data, Error::Warning(Warning::IgnoredPragma));
+    }
+
     #[test]
     fn test_strings() {
         let assert_str = |s, expected: &str| {
@@ -440,4 +873,84 @@ pub(crate) mod test {
         let newlines = " \"a\" \n \"b\" ";
         assert_str(newlines, "ab");
     }
+
+    #[test]
+    fn test_match_kind() {
+        use crate::data::lex::TokenKind;
+
+        let mut p = parser("x 1 y");
+        assert!(p.match_kind(TokenKind::Literal).is_none());
+        let id = p.match_kind(TokenKind::Id).unwrap();
+        assert_eq!(id.data, Token::Id("x".into()));
+        let int = p.match_kind(TokenKind::Literal).unwrap();
+        match int.data {
+            Token::Literal(LiteralToken::Int(i)) => assert_eq!(i.as_str(), "1"),
+            other => panic!("expected integer literal, got {}", other),
+        }
+        let id = p.match_any_kind(&[TokenKind::Literal, TokenKind::Id]);
+        assert_eq!(id.unwrap().data, Token::Id("y".into()));
+        assert!(p.match_kind(TokenKind::Id).is_none());
+    }
+
+    #[test]
+    fn test_expect_any() {
+        let choices = [Token::LeftParen, Token::Id(Default::default())];
+        let mut p = parser("x ( 1");
+        assert_eq!(p.expect_any(&choices).unwrap().data, Token::Id("x".into()));
+        assert_eq!(p.expect_any(&choices).unwrap().data, Token::LeftParen);
+        let err = p.expect_any(&choices).unwrap_err();
+        assert_eq!(
+            err.data.to_string(),
+            "expected one of '(', identifier; got '1'"
+        );
+        let err = p.expect_any(&choices).unwrap_err();
+        assert_eq!(
+            err.data.to_string(),
+            "expected one of '(', identifier; got '<end-of-file>'"
+        );
+    }
+
+    #[test]
+    fn test_duplicate_errors() {
+        // both `{` blocks hit the end of the file at the same place
+        let errors: Vec<_> = parser("struct s { { {;")
+            .filter_map(Result::err)
+            .map(|err| err.data.to_string())
+            .collect();
+        let unclosed = "invalid syntax: unclosed '{' delimeter at end of file";
+        assert_eq!(errors.iter().filter(|&err| err == unclosed).count(), 1);
+    }
+
+    #[test]
+    fn test_preserve_comments() {
+        use crate::data::ast::ExternalDeclaration;
+        use crate::lex::PreProcessorBuilder;
+
+        let doc = |input: &str, preserve| {
+            let cpp = PreProcessorBuilder::new(input)
+                .preserve_comments(preserve)
+                .build();
+            match Parser::new(cpp, false).next().unwrap().unwrap().data {
+                ExternalDeclaration::Declaration(decl) => decl.doc,
+                ExternalDeclaration::Function(func) => func.doc,
+            }
+        };
+        assert_eq!(doc("/* doc */\nint x;\n", true), Some("doc".into()));
+        assert_eq!(doc("/* doc */\nint x;\n", false), None);
+        assert_eq!(
+            doc("// first\n// second\nint f() {}\n", true),
+            Some("second".into())
+        );
+        assert_eq!(doc("int /* not a doc */ x;\n", true), None);
+
+        let cpp = PreProcessorBuilder::new("int x;\n/* y */\nint y;\n")
+            .preserve_comments(true)
+            .build();
+        let mut parser = Parser::new(cpp, false);
+        parser.next();
+        match parser.next().unwrap().unwrap().data {
+            ExternalDeclaration::Declaration(decl) => assert_eq!(decl.doc, Some("y".into())),
+            _ => panic!("expected a declaration"),
+        }
+    }
 }
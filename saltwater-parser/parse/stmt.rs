@@ -1,10 +1,16 @@
 use super::{Lexer, Parser, SyntaxResult};
 use crate::data::*;
 use crate::data::{
-    ast::{CompoundStatement, Declaration, Expr, ExternalDeclaration, Stmt, StmtType},
-    lex::Keyword,
+    ast::{
+        AsmOperand, Attribute, CompoundStatement, Declaration, Expr, ExprType, ExternalDeclaration,
+        Stmt, StmtType,
+    },
+    error::Warning,
+    lex::{AssignmentToken, Keyword},
 };
 
+use std::mem;
+
 type StmtResult = SyntaxResult<Stmt>;
 
 impl<I: Lexer> Parser<I> {
@@ -16,8 +22,27 @@ impl<I: Lexer> Parser<I> {
         let mut stmts = vec![];
         let mut pending_errs = vec![];
         while self.peek_token() != Some(&Token::RightBrace) {
+            // `x;;`, but not `{ ; }` or `if (x) {};`
+            if self.pedantic
+                && self.peek_token() == Some(&Token::Semicolon)
+                && stmts
+                    .last()
+                    .map_or(false, |s: &Stmt| s.data.ends_with_semicolon())
+            {
+                let location = self.next_location();
+                self.error_handler
+                    .warn(Warning::ExtraneousSemicolon("block"), location);
+            }
             match self.statement() {
                 Ok(stmt) => {
+                    // `{ x(); int y; }`
+                    if self.c89
+                        && matches!(stmt.data, StmtType::Decl(_))
+                        && stmts.iter().any(|s| !matches!(s.data, StmtType::Decl(_)))
+                    {
+                        self.error_handler
+                            .warn(Warning::MixedDeclarations, stmt.location);
+                    }
                     location = location.merge(stmt.location);
                     stmts.push(stmt);
                 }
@@ -47,10 +72,20 @@ impl<I: Lexer> Parser<I> {
     }
     fn declaration(&mut self) -> SyntaxResult<Stmt> {
         let decl = self.external_declaration()?;
-        match decl.data.into_declaration() {
-            Err(err) => Err(decl.location.with(err)),
-            Ok(declaration) => Ok(Stmt::new(StmtType::Decl(declaration), decl.location)),
+        Ok(decl
+            .and_then(ExternalDeclaration::into_declaration)?
+            .map(StmtType::Decl))
+    }
+    /// Parse a single statement, such as `if (x) y;`, outside of any function.
+    ///
+    /// Unlike `statement`, this is an error if there are any tokens left afterwards.
+    pub fn parse_statement(&mut self) -> SyntaxResult<Stmt> {
+        let stmt = self.statement()?;
+        if let Some(token) = self.peek_token() {
+            let err = SyntaxError::TrailingTokens(token.clone());
+            return Err(self.next_location().with(err));
         }
+        Ok(stmt)
     }
     /// ```yacc
     /// statement
@@ -72,8 +107,20 @@ impl<I: Lexer> Parser<I> {
         let _guard = self.recursion_check();
         // take out 2 guards since this goes through `compound_statement` before calling itself again
         let _guard2 = self.recursion_check();
+        if self.peek_token() == Some(&Token::LeftBracket)
+            && self.peek_next_token() == Some(&Token::LeftBracket)
+        {
+            return self.attributed_statement();
+        }
         match self.peek_token() {
             Some(Token::LeftBrace) => Ok(self.compound_statement()?.map(StmtType::Compound)),
+            Some(Token::Pragma(_)) => {
+                let pragma = self.next_token().unwrap();
+                Ok(pragma.map(|token| match token {
+                    Token::Pragma(pragma) => StmtType::Pragma(pragma),
+                    _ => unreachable!("peek should always be the same as next"),
+                }))
+            }
             Some(Token::Keyword(k)) => match k {
                 // labeled_statement (excluding labels)
                 Keyword::Case => {
@@ -125,12 +172,18 @@ impl<I: Lexer> Parser<I> {
                 }
                 Keyword::Return => self.return_statement(),
 
+                // GNU extension
+                Keyword::Asm => self.asm_statement(),
+
                 // start of an expression statement
                 Keyword::Sizeof
                 | Keyword::StaticAssert
                 | Keyword::Alignas
                 | Keyword::Alignof
-                | Keyword::Generic => self.expression_statement(),
+                | Keyword::GnuAlignof
+                | Keyword::Generic
+                | Keyword::TypesCompatible
+                | Keyword::ChooseExpr => self.expression_statement(),
                 decl if decl.is_decl_specifier() => self.declaration(),
                 other => {
                     let err = SyntaxError::NotAStatement(*other);
@@ -145,25 +198,31 @@ impl<I: Lexer> Parser<I> {
                 })
             }
             Some(Token::Id(_)) => {
-                let locatable = self.next_token().unwrap();
-                let id = match locatable.data {
-                    Token::Id(id) => Locatable {
-                        data: id,
-                        location: locatable.location,
-                    },
+                let id = self.next_token().unwrap().map(|token| match token {
+                    Token::Id(id) => id,
                     _ => unreachable!("peek should always be the same as next"),
-                };
+                });
                 if self.match_next(&Token::Colon).is_some() {
+                    // `{ done: }`: before C23, a label has to be followed by a statement
+                    let body = if self.peek_token() == Some(&Token::RightBrace) {
+                        if !self.c23 {
+                            self.error_handler
+                                .warn(Warning::LabelAtEndOfBlock, id.location);
+                        }
+                        Stmt {
+                            data: Default::default(),
+                            location: id.location,
+                        }
+                    } else {
+                        self.statement()?
+                    };
                     return Ok(Stmt {
-                        data: StmtType::Label(id.data, Box::new(self.statement()?)),
+                        data: StmtType::Label(id.data, Box::new(body)),
                         location: id.location,
                     });
                 }
                 let is_typedef = self.typedefs.get(&id.data).is_some();
-                self.unput(Some(Locatable {
-                    data: Token::Id(id.data),
-                    location: id.location,
-                }));
+                self.unput(Some(id.map(Token::Id)));
                 if is_typedef {
                     self.declaration()
                 } else {
@@ -193,33 +252,167 @@ impl<I: Lexer> Parser<I> {
             data: StmtType::Return(expr),
         })
     }
+    /// asm_statement:
+    ///     ASM VOLATILE? '(' string (':' asm_operands (':' asm_operands (':' clobbers)?)?)? ')' ';'
+    ///
+    /// asm_operands:
+    ///     (string '(' expr ')' (',' string '(' expr ')')*)?
+    ///
+    /// clobbers:
+    ///     (string (',' string)*)?
+    fn asm_statement(&mut self) -> StmtResult {
+        let start = self
+            .expect(Token::Keyword(Keyword::Asm))
+            .expect("parser shouldn't call asm_statement without an asm");
+        let volatile = self.match_keywords(&[Keyword::Volatile]).is_some();
+        self.expect(Token::LeftParen)?;
+        let template = self.string_literal()?;
+        let (mut outputs, mut inputs, mut clobbers) = (vec![], vec![], vec![]);
+        if self.match_next(&Token::Colon).is_some() {
+            outputs = self.asm_operands()?;
+            if self.match_next(&Token::Colon).is_some() {
+                inputs = self.asm_operands()?;
+                if self.match_next(&Token::Colon).is_some() {
+                    while self.peek_string_literal() {
+                        clobbers.push(self.string_literal()?);
+                        if self.match_next(&Token::Comma).is_none() {
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+        let end = self.expect(Token::RightParen)?;
+        self.expect(Token::Semicolon)?;
+        Ok(Stmt {
+            data: StmtType::Asm {
+                template,
+                volatile,
+                outputs,
+                inputs,
+                clobbers,
+            },
+            location: start.location.merge(end.location),
+        })
+    }
+    fn asm_operands(&mut self) -> SyntaxResult<Vec<AsmOperand>> {
+        let mut operands = vec![];
+        while self.peek_string_literal() {
+            let constraint = self.string_literal()?;
+            self.expect(Token::LeftParen)?;
+            let expr = self.expr()?;
+            self.expect(Token::RightParen)?;
+            operands.push(AsmOperand { constraint, expr });
+            if self.match_next(&Token::Comma).is_none() {
+                break;
+            }
+        }
+        Ok(operands)
+    }
+    fn peek_string_literal(&mut self) -> bool {
+        matches!(
+            self.peek_token(),
+            Some(Token::Literal(LiteralToken::Str(_)))
+        )
+    }
+    /// Parse a string literal, without the trailing NUL byte.
+    pub(super) fn string_literal(&mut self) -> SyntaxResult<String> {
+        if !self.peek_string_literal() {
+            return Err(self.next_location().with(SyntaxError::ExpectedString));
+        }
+        let literal = self.match_literal().unwrap().data;
+        let mut bytes = match literal.parse() {
+            Ok(LiteralValue::Str(bytes)) => bytes,
+            _ => unreachable!("string literals should always parse to strings"),
+        };
+        assert_eq!(bytes.pop(), Some(b'\0'));
+        Ok(String::from_utf8_lossy(&bytes).into_owned())
+    }
+    /// A statement beginning with standard attributes, like `[[fallthrough]];`
+    fn attributed_statement(&mut self) -> StmtResult {
+        let start = self.next_location();
+        self.peek_significant();
+        // [[fallthrough]];
+        if let Some(semicolon) = self.match_next(&Token::Semicolon) {
+            let mut fallthrough = false;
+            for attr in mem::take(&mut self.attributes) {
+                if attr.data == Attribute::Fallthrough {
+                    fallthrough = true;
+                } else {
+                    self.misplaced_attribute(attr, "cannot be applied to a statement");
+                }
+            }
+            let location = start.merge(semicolon.location);
+            if !fallthrough {
+                return Ok(Stmt::new(Default::default(), location));
+            }
+            if !matches!(
+                self.peek_token(),
+                Some(Token::Keyword(Keyword::Case)) | Some(Token::Keyword(Keyword::Default))
+            ) {
+                let attr = Locatable::new(Attribute::Fallthrough, location);
+                self.misplaced_attribute(attr, "must be followed by a case label");
+            }
+            return Ok(Stmt::new(StmtType::Fallthrough, location));
+        }
+        // [[maybe_unused]] int x;
+        if self.starts_declaration() {
+            return self.declaration();
+        }
+        for attr in mem::take(&mut self.attributes) {
+            self.misplaced_attribute(attr, "cannot be applied to a statement");
+        }
+        self.statement()
+    }
+    fn misplaced_attribute(&mut self, attr: Locatable<Attribute>, reason: &'static str) {
+        let err = SyntaxError::MisplacedAttribute(attr.data, reason);
+        self.error_handler.error(err, attr.location);
+    }
     /// if_statement:
     ///     IF '(' expr ')' statement
     ///   | IF '(' expr ')' statement ELSE statement
     fn if_statement(&mut self) -> StmtResult {
-        let start = self
-            .expect(Token::Keyword(Keyword::If))
-            .expect("parser shouldn't call if_statement without an if");
-        self.expect(Token::LeftParen)?;
-        let condition = self.expr()?;
-        self.expect(Token::RightParen)?;
-        let body = self.statement()?;
-        let otherwise = if self.match_next(&Token::Keyword(Keyword::Else)).is_some() {
-            // NOTE: `if (1) ; else ;` is legal!
-            Some(Box::new(self.statement()?))
-        } else {
-            None
+        // `else if` chains are parsed in a loop instead of recursively,
+        // so that long chains don't hit the recursion limit.
+        let mut chain = Vec::new();
+        let mut otherwise = None;
+        loop {
+            let start = self
+                .expect(Token::Keyword(Keyword::If))
+                .expect("parser shouldn't call if_statement without an if");
+            self.expect(Token::LeftParen)?;
+            let condition = self.condition(Keyword::If)?;
+            self.expect(Token::RightParen)?;
+            let body = self.statement()?;
+            chain.push((start.location, condition, body));
+            if self.match_next(&Token::Keyword(Keyword::Else)).is_none() {
+                break;
+            }
+            if self.peek_token() != Some(&Token::Keyword(Keyword::If)) {
+                // NOTE: `if (1) ; else ;` is legal!
+                otherwise = Some(Box::new(self.statement()?));
+                break;
+            }
+        }
+        // build the tree from the innermost `else` outwards
+        let (location, condition, body) = chain.pop().unwrap();
+        let mut stmt = Stmt {
+            data: StmtType::If(condition, Box::new(body), otherwise),
+            location,
         };
-        let stmt = StmtType::If(condition, Box::new(body), otherwise);
-        Ok(Stmt {
-            data: stmt,
-            location: start.location,
-        })
+        while let Some((location, condition, body)) = chain.pop() {
+            stmt = Stmt {
+                data: StmtType::If(condition, Box::new(body), Some(Box::new(stmt))),
+                location,
+            };
+        }
+        Ok(stmt)
     }
     /// switch_statement: SWITCH '(' expr ')' statement
     fn switch_statement(&mut self) -> StmtResult {
         let start = self.expect(Token::Keyword(Keyword::Switch))?;
         self.expect(Token::LeftParen)?;
+        self.reject_declaration(Keyword::Switch)?;
         let expr = self.expr()?;
         self.expect(Token::RightParen)?;
         let body = self.statement()?;
@@ -233,7 +426,7 @@ impl<I: Lexer> Parser<I> {
     fn while_statement(&mut self) -> StmtResult {
         let start = self.expect(Token::Keyword(Keyword::While))?;
         self.expect(Token::LeftParen)?;
-        let condition = self.expr()?;
+        let condition = self.condition(Keyword::While)?;
         self.expect(Token::RightParen)?;
         let body = self.statement()?;
         Ok(Stmt {
@@ -252,7 +445,7 @@ impl<I: Lexer> Parser<I> {
         let body = self.statement()?;
         self.expect(Token::Keyword(Keyword::While))?;
         self.expect(Token::LeftParen)?;
-        let condition = self.expr()?;
+        let condition = self.condition(Keyword::Do)?;
         self.expect(Token::RightParen)?;
         self.expect(Token::Semicolon)?;
         let stmt = StmtType::Do(Box::new(body), condition);
@@ -319,6 +512,9 @@ impl<I: Lexer> Parser<I> {
             location: paren.location.merge(decl_stmt.location),
         });
         let controlling_expr = self.expr_opt(Token::Semicolon)?;
+        if let Some(condition) = &controlling_expr {
+            self.warn_if_assignment(condition);
+        }
         let iter_expr = self.expr_opt(Token::RightParen)?;
         let body = Box::new(self.statement()?);
         Ok(Stmt {
@@ -331,6 +527,44 @@ impl<I: Lexer> Parser<I> {
             location: start.location,
         })
     }
+    /// Return whether the next token is a declaration specifier or typedef name.
+    pub(super) fn starts_declaration(&mut self) -> bool {
+        match self.peek_token() {
+            Some(Token::Keyword(k)) => k.is_decl_specifier(),
+            Some(&Token::Id(id)) => self.typedefs.get(&id).is_some(),
+            _ => false,
+        }
+    }
+    /// C++ allows `switch (int x = f())`, but C doesn't.
+    ///
+    /// Check for this up front so we give a better error than 'expected expression'.
+    fn reject_declaration(&mut self, keyword: Keyword) -> SyntaxResult<()> {
+        if self.starts_declaration() {
+            let err = SyntaxError::DeclarationInCondition(keyword);
+            Err(self.next_location().with(err))
+        } else {
+            Ok(())
+        }
+    }
+    /// The controlling expression of an `if`, `while`, or `do`
+    fn condition(&mut self, keyword: Keyword) -> SyntaxResult<Expr> {
+        self.reject_declaration(keyword)?;
+        let condition = self.expr()?;
+        self.warn_if_assignment(&condition);
+        Ok(condition)
+    }
+    /// Warn on `if (x = 0)`, which was probably meant to be `if (x == 0)`.
+    ///
+    /// Parenthesized expressions have their location extended to cover the parentheses,
+    /// so `if ((x = 0))` can be told apart by checking whether it starts before `x`.
+    fn warn_if_assignment(&mut self, condition: &Expr) {
+        if let ExprType::Assign(left, _, AssignmentToken::Equal) = &condition.data {
+            if condition.location.span.start == left.location.span.start {
+                self.error_handler
+                    .warn(Warning::AssignmentInCondition, condition.location);
+            }
+        }
+    }
     /// `goto_statement: GOTO identifier ';'`
     ///
     /// <http://www.quut.com/c/ANSI-C-grammar-y.html#jump_statement>
@@ -350,12 +584,40 @@ impl ExternalDeclaration {
     /// Otherwise, return the declarator for the function definition.
     fn into_declaration(self) -> Result<Declaration, SyntaxError> {
         match self {
-            ExternalDeclaration::Function(def) => Err(SyntaxError::FunctionNotAllowed(def)),
+            ExternalDeclaration::Function(def) => {
+                Err(SyntaxError::FunctionNotAllowed(Box::new(def)))
+            }
             ExternalDeclaration::Declaration(decl) => Ok(decl),
         }
     }
 }
 
+impl StmtType {
+    /// Whether this statement was terminated by a `;`,
+    /// in which case another `;` directly after it is almost certainly a typo.
+    fn ends_with_semicolon(&self) -> bool {
+        use StmtType::*;
+        match self {
+            Expr(_)
+            | Decl(_)
+            | Goto(_)
+            | Continue
+            | Break
+            | Return(_)
+            | Do(..)
+            | Fallthrough
+            | Asm { .. } => true,
+            If(_, body, None) | While(_, body) | For { body, .. } | Switch(_, body) => {
+                body.data.ends_with_semicolon()
+            }
+            If(_, _, Some(body)) | Label(_, body) | Case(_, body) | Default(body) => {
+                body.data.ends_with_semicolon()
+            }
+            Compound(_) | Pragma(_) => false,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::data::ast::*;
@@ -395,6 +657,28 @@ mod tests {
         assert_eq!(parsed.unwrap().location, expected.unwrap().location);
     }
     #[test]
+    fn test_parse_statement() {
+        let parse = |s| {
+            let mut p = parser(s);
+            let stmt = p.parse_statement();
+            assert!(p.error_handler.pop_front().is_none());
+            stmt
+        };
+        assert_eq!(parse("return 0;").unwrap().data.to_string(), "return 0;");
+        match parse("{ int x; }").unwrap().data {
+            StmtType::Compound(stmts) => assert_eq!(stmts.len(), 1),
+            other => panic!("expected a compound statement, got {}", other),
+        }
+        assert_eq!(
+            parse("if (a) b; else c;").unwrap().data.to_string(),
+            "if (a) b; else c;"
+        );
+        assert_eq!(
+            parse("return 0; x").unwrap_err().data,
+            SyntaxError::TrailingTokens(Token::Id("x".into()))
+        );
+    }
+    #[test]
     fn test_goto() {
         assert_no_change("goto a;");
     }
@@ -407,6 +691,35 @@ mod tests {
         assert_no_change("if (1) break; else continue;");
     }
     #[test]
+    fn test_else_if_chain() {
+        const DEPTH: usize = 10_000;
+        let mut program = String::from("if (0) ;");
+        for i in 1..DEPTH {
+            program.push_str(&format!(" else if ({}) ;", i));
+        }
+        program.push_str(" else break;");
+        let mut current = stmt(&program).unwrap();
+        // walk the tree by value so it isn't dropped recursively
+        for _ in 1..DEPTH {
+            current = match current.data {
+                StmtType::If(_, _, Some(otherwise)) => *otherwise,
+                other => panic!("expected else if, got {}", other),
+            };
+        }
+        match current.data {
+            StmtType::If(_, _, Some(otherwise)) => assert_eq!(otherwise.data, StmtType::Break),
+            other => panic!("expected if, got {}", other),
+        }
+        // dangling else still binds to the nearest if
+        match stmt("if (1) if (2) ; else break;").unwrap().data {
+            StmtType::If(_, inner, None) => match inner.data {
+                StmtType::If(_, _, Some(_)) => {}
+                other => panic!("else bound to wrong if: {}", other),
+            },
+            other => panic!("expected if, got {}", other),
+        }
+    }
+    #[test]
     fn test_while() {
         assert_stmt_display("while(1);", "while (1) {\n}");
         assert_no_change("while (1) {\n}");
@@ -449,4 +762,227 @@ mod tests {
 }",
         );
     }
+    #[test]
+    fn test_extra_semicolon() {
+        use crate::data::error::Warning;
+
+        let warnings = |s| {
+            let mut p = parser(s).pedantic(true);
+            p.compound_statement().unwrap();
+            assert!(p.error_handler.pop_front().is_none());
+            p.warnings().into_iter().map(|w| w.data).collect::<Vec<_>>()
+        };
+        let extra = Warning::ExtraneousSemicolon("block");
+        // only warn if asked to
+        let mut p = parser("{ x;; }");
+        p.compound_statement().unwrap();
+        assert!(p.warnings().is_empty());
+        assert_eq!(warnings("{ x;; }"), vec![extra.clone()]);
+        assert_eq!(warnings("{ return;;; }"), vec![extra.clone()]);
+        assert_eq!(warnings("{ if (1) x;; }"), vec![extra]);
+        assert!(warnings("{ ; }").is_empty());
+        assert!(warnings("{ {}; }").is_empty());
+        assert!(warnings("{ while (1); }").is_empty());
+    }
+    #[test]
+    fn test_assignment_in_condition() {
+        use crate::data::error::Warning;
+
+        let warnings = |s| {
+            let mut p = parser(s);
+            p.statement().unwrap();
+            assert!(p.error_handler.pop_front().is_none());
+            p.warnings().into_iter().map(|w| w.data).collect::<Vec<_>>()
+        };
+        let assign = Warning::AssignmentInCondition;
+        assert_eq!(warnings("if (x = 0);"), vec![assign.clone()]);
+        assert_eq!(warnings("while (x = 0);"), vec![assign.clone()]);
+        assert_eq!(warnings("do ; while (x = 0);"), vec![assign.clone()]);
+        assert_eq!(warnings("for (; x = 0;);"), vec![assign.clone()]);
+        assert_eq!(warnings("if ((x) = 0);"), vec![assign]);
+        assert!(warnings("if ((x = 0));").is_empty());
+        assert!(warnings("if (x == 0);").is_empty());
+        assert!(warnings("if (x += 1);").is_empty());
+        assert!(warnings("for (x = 0;;);").is_empty());
+    }
+    #[test]
+    fn test_mixed_declarations() {
+        use crate::data::error::Warning;
+
+        let warnings = |s, c89| {
+            let mut p = parser(s).c89(c89);
+            p.compound_statement().unwrap();
+            assert!(p.error_handler.pop_front().is_none());
+            p.warnings().into_iter().map(|w| w.data).collect::<Vec<_>>()
+        };
+        assert_eq!(
+            warnings("{ x(); int y; }", true),
+            vec![Warning::MixedDeclarations]
+        );
+        assert!(warnings("{ x(); int y; }", false).is_empty());
+        assert!(warnings("{ int x; int y; x = y; }", true).is_empty());
+        // nested blocks are checked separately
+        assert!(warnings("{ int x; { x; } }", true).is_empty());
+    }
+    #[test]
+    fn test_label_at_end_of_block() {
+        use crate::data::error::Warning;
+
+        let parse = |s, c23| {
+            let mut p = parser(s).c23(c23);
+            let stmts = p.compound_statement().unwrap().data;
+            assert!(p.error_handler.pop_front().is_none());
+            let warnings: Vec<_> = p.warnings().into_iter().map(|w| w.data).collect();
+            (stmts, warnings)
+        };
+        let null = Box::new(Location::default().with(StmtType::default()));
+        let (stmts, warnings) = parse("{ end: }", true);
+        assert_eq!(
+            stmts,
+            vec![Location::default().with(StmtType::Label("end".into(), null))]
+        );
+        assert!(warnings.is_empty());
+
+        let (stmts, warnings) = parse("{ x(); end: }", false);
+        assert_eq!(stmts.len(), 2);
+        assert_eq!(warnings, vec![Warning::LabelAtEndOfBlock]);
+        // a label followed by a statement is fine in any standard
+        assert!(parse("{ end: ; }", false).1.is_empty());
+    }
+    #[test]
+    fn test_fallthrough() {
+        let switch = stmt("switch (1) { case 1: x; [[fallthrough]]; case 2: break; }").unwrap();
+        assert_eq!(
+            switch.data.to_string(),
+            "switch (1) {\n    case 1:\n        x;\n    [[fallthrough]];\n    case 2:\n        break;\n}"
+        );
+        assert!(stmt("switch (1) { case 1: [[fallthrough]]; default: ; }").is_ok());
+        // attributes on declarations in blocks
+        match stmt("[[maybe_unused]] int x;").unwrap().data {
+            StmtType::Decl(decl) => assert_eq!(decl.attributes[0].data, Attribute::MaybeUnused),
+            other => panic!("expected declaration, got {}", other),
+        }
+
+        let misplaced = |s| match stmt(s).unwrap_err().data {
+            Error::Syntax(SyntaxError::MisplacedAttribute(attr, _)) => attr,
+            other => panic!("expected misplaced attribute, got {}", other),
+        };
+        assert_eq!(misplaced("{ [[fallthrough]]; x; }"), Attribute::Fallthrough);
+        assert_eq!(misplaced("[[fallthrough]] x;"), Attribute::Fallthrough);
+        assert_eq!(misplaced("[[noreturn]];"), Attribute::NoReturn);
+    }
+    #[test]
+    fn test_declaration_in_condition() {
+        use crate::data::lex::Keyword;
+
+        let err = |s| stmt(s).unwrap_err().data;
+        assert_eq!(
+            err("switch (int x = 1) {}"),
+            SyntaxError::DeclarationInCondition(Keyword::Switch).into()
+        );
+        assert_eq!(
+            err("if (const int x = 1) ;"),
+            SyntaxError::DeclarationInCondition(Keyword::If).into()
+        );
+        assert_eq!(
+            err("while (int x = 1) ;"),
+            SyntaxError::DeclarationInCondition(Keyword::While).into()
+        );
+        assert!(stmt("switch (x) {}").is_ok());
+        assert!(stmt("switch ((int)x) {}").is_ok());
+        assert!(stmt("switch (sizeof(int)) {}").is_ok());
+    }
+    #[test]
+    fn test_asm() {
+        use crate::{Parser, PreProcessorBuilder};
+
+        // plain `asm` is only a keyword in GNU mode
+        let asm = |s| {
+            let cpp = PreProcessorBuilder::new(s).gnu_extensions(true).build();
+            Parser::new(cpp, false).statement().unwrap().data
+        };
+        match asm("asm(\"nop\");") {
+            StmtType::Asm {
+                template,
+                volatile: false,
+                outputs,
+                inputs,
+                clobbers,
+            } => {
+                assert_eq!(template, "nop");
+                assert!(outputs.is_empty() && inputs.is_empty() && clobbers.is_empty());
+            }
+            other => panic!("expected asm, got {}", other),
+        }
+        match asm("__asm__ volatile (\"mov $1, %0\" : \"=r\"(x) : : \"memory\");") {
+            StmtType::Asm {
+                template,
+                volatile: true,
+                outputs,
+                inputs,
+                clobbers,
+            } => {
+                assert_eq!(template, "mov $1, %0");
+                assert_eq!(outputs.len(), 1);
+                assert_eq!(outputs[0].constraint, "=r");
+                assert_eq!(outputs[0].expr.to_string(), "x");
+                assert!(inputs.is_empty());
+                assert_eq!(clobbers, vec!["memory".to_string()]);
+            }
+            other => panic!("expected asm, got {}", other),
+        }
+        assert_eq!(
+            asm("asm(\"add %1, %0\" : \"+r\"(a) : \"r\"(b), \"i\"(1));").to_string(),
+            "asm (\"add %1, %0\" : \"+r\"(a) : \"r\"(b), \"i\"(1) : );"
+        );
+        assert_eq!(
+            stmt("__asm__(nop);").unwrap_err().data,
+            SyntaxError::ExpectedString.into()
+        );
+        assert!(stmt("__asm(\"nop\" : \"=r\" x);").is_err());
+        // in ISO C, `asm` is an ordinary identifier
+        assert_stmt_display("int asm;", "int asm;");
+        assert_stmt_display("asm(\"nop\");", "(asm)(\"nop\");");
+    }
+    #[test]
+    fn test_pragma() {
+        assert_eq!(
+            stmt("_Pragma(\"once\")").unwrap().data,
+            StmtType::Pragma("once".into())
+        );
+        // pragmas are kept where they appear
+        match stmt("{ x; _Pragma(\"unroll 4\") y; }").unwrap().data {
+            StmtType::Compound(stmts) => {
+                assert_eq!(stmts[1].data, StmtType::Pragma("unroll 4".into()))
+            }
+            other => panic!("expected a compound statement, got {}", other),
+        }
+        assert_stmt_display("_Pragma(\"once\")", "_Pragma(\"once\")");
+    }
+    #[test]
+    fn test_missing_semicolon() {
+        let mut p = parser("{ int x\nint y; }");
+        let block = p.compound_statement().unwrap();
+        // recovered, so both declarations are still there
+        assert_eq!(block.data.len(), 2);
+        assert_eq!(
+            p.error_handler.pop_front().unwrap().data,
+            SyntaxError::MissingSemicolon.into()
+        );
+        assert!(p.error_handler.pop_front().is_none());
+        // the recovery isn't mistaken for an empty statement
+        assert!(p.warnings().is_empty());
+
+        // typedef names start a declaration too
+        for src in &["typedef int T; T x\nT y;", "typedef int T\nT y;"] {
+            let results: Vec<_> = parser(src).collect();
+            let errors: Vec<_> = results.iter().filter_map(|r| r.as_ref().err()).collect();
+            assert_eq!(errors.len(), 1, "{}: {:?}", src, errors);
+            assert_eq!(errors[0].data, SyntaxError::MissingSemicolon.into());
+            assert_eq!(
+                results.last().unwrap().as_ref().unwrap().data.to_string(),
+                "T y;"
+            );
+        }
+    }
 }
@@ -40,9 +40,12 @@ FLAGS:
                             Note that preprocessing discards whitespace and comments.
                             There is not currently a way to disable this behavior.
     -V, --version          Prints version information
+        --warnings-as-errors
+                           If set, treat all warnings as errors.
 
 OPTIONS:
         --color <when>       When to use color. May be \"never\", \"auto\", or \"always\". [default: auto]
+        --tab-width <n>      The number of columns between tab stops, used for reporting columns. [default: 8]
     -o, --output <output>    The output file to use. [default: a.out]
         --max-errors <max>   The maximum number of errors to allow before giving up.
                              Use 0 to allow unlimited errors. [default: 10]
@@ -60,6 +63,7 @@ ARGS:
 const USAGE: &str = "\
 usage: swcc [--help | -h] [--version | -V] [--debug-ir] [--debug-ast] [--debug-lex]
             [--debug-hir] [--jit] [--no-link | -c] [--preprocess-only | -E]
+            [--warnings-as-errors]
             [-I <dir>] [-D <id[=val]>] [<file>]";
 
 struct BinOpt {
@@ -72,6 +76,8 @@ struct BinOpt {
     preprocess_only: bool,
     /// Whether or not to use color
     color: ColorChoice,
+    /// The number of columns between tab stops when reporting locations
+    tab_width: u32,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -123,7 +129,7 @@ fn real_main(buf: ArcStr, bin_opt: BinOpt, output: &Path) -> Result<(), (Error,
             warnings,
             files,
         } = preprocess(&buf, bin_opt.opt);
-        handle_warnings(warnings, &files, bin_opt.color);
+        handle_warnings(warnings, &files, bin_opt.color, bin_opt.tab_width);
 
         let stdout = io::stdout();
         let mut stdout_buf = BufWriter::new(stdout.lock());
@@ -138,7 +144,7 @@ fn real_main(buf: ArcStr, bin_opt: BinOpt, output: &Path) -> Result<(), (Error,
     #[cfg(feature = "jit")]
     {
         if !opt.jit {
-            aot_main(&buf, opt, output, bin_opt.color)
+            aot_main(&buf, opt, output, bin_opt.color, bin_opt.tab_width)
         } else {
             let module = saltwater_codegen::initialize_jit_module();
             let Program {
@@ -146,7 +152,7 @@ fn real_main(buf: ArcStr, bin_opt: BinOpt, output: &Path) -> Result<(), (Error,
                 warnings,
                 files,
             } = compile(module, &buf, opt);
-            handle_warnings(warnings, &files, bin_opt.color);
+            handle_warnings(warnings, &files, bin_opt.color, bin_opt.tab_width);
             let mut jit = saltwater_codegen::JIT::from(sw_try!(result, files));
             if let Some(exit_code) = unsafe { jit.run_main() } {
                 std::process::exit(exit_code);
@@ -155,11 +161,17 @@ fn real_main(buf: ArcStr, bin_opt: BinOpt, output: &Path) -> Result<(), (Error,
         }
     }
     #[cfg(not(feature = "jit"))]
-    aot_main(&buf, opt, output, bin_opt.color)
+    aot_main(&buf, opt, output, bin_opt.color, bin_opt.tab_width)
 }
 
 #[inline]
-fn aot_main(buf: &str, opt: Opt, output: &Path, color: ColorChoice) -> Result<(), (Error, Files)> {
+fn aot_main(
+    buf: &str,
+    opt: Opt,
+    output: &Path,
+    color: ColorChoice,
+    tab_width: u32,
+) -> Result<(), (Error, Files)> {
     let no_link = opt.no_link;
     let module = saltwater_codegen::initialize_aot_module("saltwater_main".to_owned());
     let Program {
@@ -167,7 +179,7 @@ fn aot_main(buf: &str, opt: Opt, output: &Path, color: ColorChoice) -> Result<()
         warnings,
         files,
     } = compile(module, buf, opt);
-    handle_warnings(warnings, &files, color);
+    handle_warnings(warnings, &files, color, tab_width);
 
     let product = sw_try!(result.map(|x| x.finish()), files);
     if no_link {
@@ -180,7 +192,12 @@ fn aot_main(buf: &str, opt: Opt, output: &Path, color: ColorChoice) -> Result<()
     Ok(())
 }
 
-fn handle_warnings(warnings: VecDeque<CompileWarning>, file_db: &Files, color: ColorChoice) {
+fn handle_warnings(
+    warnings: VecDeque<CompileWarning>,
+    file_db: &Files,
+    color: ColorChoice,
+    tab_width: u32,
+) {
     WARNINGS.fetch_add(warnings.len(), Ordering::Relaxed);
     #[cfg(not(feature = "salty"))]
     let warn = "warning";
@@ -216,7 +233,13 @@ fn handle_warnings(warnings: VecDeque<CompileWarning>, file_db: &Files, color: C
     for warning in warnings {
         print!(
             "{}",
-            pretty_print(tag.clone(), warning.data, warning.location, file_db)
+            pretty_print(
+                tag.clone(),
+                warning.data,
+                warning.location,
+                file_db,
+                tab_width
+            )
         );
     }
 }
@@ -266,9 +289,9 @@ fn main() {
     };
     let buf: ArcStr = buf.into();
     let max_errors = opt.opt.max_errors;
-    let color_choice = opt.color;
+    let (color_choice, tab_width) = (opt.color, opt.tab_width);
     real_main(buf, opt, &output)
-        .unwrap_or_else(|(err, files)| err_exit(err, max_errors, color_choice, &files));
+        .unwrap_or_else(|(err, files)| err_exit(err, max_errors, color_choice, tab_width, &files));
 }
 
 fn str_to_path_buf(s: &str) -> Result<PathBuf, bool> {
@@ -334,6 +357,7 @@ fn parse_args() -> Result<(BinOpt, PathBuf), pico_args::Error> {
     let color_choice = input
         .opt_value_from_str("--color")?
         .unwrap_or(ColorChoice::Auto);
+    let tab_width = input.opt_value_from_str("--tab-width")?.unwrap_or(8);
     let mut search_path = Vec::new();
     while let Some(include) = input.opt_value_from_fn(["-I", "--include"], str_to_path_buf)? {
         search_path.push(include);
@@ -366,6 +390,7 @@ fn parse_args() -> Result<(BinOpt, PathBuf), pico_args::Error> {
             no_link: input.contains(["-c", "--no-link"]),
             #[cfg(feature = "jit")]
             jit: input.contains("--jit"),
+            warnings_as_errors: input.contains("--warnings-as-errors"),
             max_errors,
             definitions,
             search_path,
@@ -376,16 +401,26 @@ fn parse_args() -> Result<(BinOpt, PathBuf), pico_args::Error> {
                 .unwrap_or_else(|| "-".into()),
         },
         color: color_choice,
+        tab_width,
     };
     Ok((bin_opt, output))
 }
 
-fn err_exit(err: Error, max_errors: Option<NonZeroUsize>, color: ColorChoice, files: &Files) -> ! {
+fn err_exit(
+    err: Error,
+    max_errors: Option<NonZeroUsize>,
+    color: ColorChoice,
+    tab_width: u32,
+    files: &Files,
+) -> ! {
     use Error::*;
     match err {
         Source(errs) => {
             for err in &errs {
-                error(&err.data, err.location(), files, color);
+                error(&err.data, err.location(), files, color, tab_width);
+                if let Some((msg, location)) = err.data.note() {
+                    note(msg, location, files, color, tab_width);
+                }
             }
             if let Some(max) = max_errors {
                 if usize::from(max) <= errs.len() {
@@ -418,7 +453,13 @@ fn print_issues(warnings: usize, errors: usize) {
     eprintln!("{} generated", msg);
 }
 
-fn error<T: std::fmt::Display>(msg: T, location: Location, file_db: &Files, color: ColorChoice) {
+fn error<T: std::fmt::Display>(
+    msg: T,
+    location: Location,
+    file_db: &Files,
+    color: ColorChoice,
+    tab_width: u32,
+) {
     ERRORS.fetch_add(1, Ordering::Relaxed);
     #[cfg(not(feature = "salty"))]
     let err = "error";
@@ -466,7 +507,28 @@ fn error<T: std::fmt::Display>(msg: T, location: Location, file_db: &Files, colo
     } else {
         ANSIString::from(err)
     };
-    print!("{}", pretty_print(prefix, msg, location, file_db,));
+    print!(
+        "{}",
+        pretty_print(prefix, msg, location, file_db, tab_width)
+    );
+}
+
+fn note<T: std::fmt::Display>(
+    msg: T,
+    location: Location,
+    file_db: &Files,
+    color: ColorChoice,
+    tab_width: u32,
+) {
+    let prefix = if color.use_color_for(atty::Stream::Stdout) {
+        Colour::Cyan.bold().paint("note")
+    } else {
+        ANSIString::from("note")
+    };
+    print!(
+        "{}",
+        pretty_print(prefix, msg, location, file_db, tab_width)
+    );
 }
 
 #[must_use]
@@ -475,16 +537,26 @@ fn pretty_print<T: std::fmt::Display>(
     msg: T,
     location: Location,
     file_db: &Files,
+    tab_width: u32,
 ) -> String {
     let file = location.file;
     let start = file_db
         .location(file, location.span.start)
         .expect("start location should be in bounds");
+    let line = file_db
+        .line_span(file, start.line)
+        .expect("line should be in bounds");
+    let source_line = file_db.source_slice(file, line).unwrap();
+    // everything on the same line before the start of the location
+    let before = &source_line[..(location.span.start - line.start().0) as usize];
+    let (line_number, column) = location
+        .line_column(file_db, 1, tab_width)
+        .expect("start location should be in bounds");
     let buf = format!(
         "{}:{}:{} {}: {}\n",
         file_db.name(file).to_string_lossy(),
-        start.line.number(),
-        start.column.number(),
+        line_number,
+        column,
         prefix,
         msg
     );
@@ -496,14 +568,16 @@ fn pretty_print<T: std::fmt::Display>(
         .location(file, location.span.end)
         .expect("end location should be in bounds");
     if start.line == end.line {
-        let line = file_db
-            .line_span(file, start.line)
-            .expect("line should be in bounds");
+        // keep tabs so the caret lines up no matter how the terminal displays them
+        let indent: String = before
+            .chars()
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
         format!(
             "{}{}{}{}\n",
             buf,
-            file_db.source_slice(file, line).unwrap(),
-            " ".repeat(start.column.0 as usize),
+            source_line,
+            indent,
             "^".repeat((end.column - start.column).0 as usize)
         )
     } else {
@@ -614,7 +688,7 @@ mod test {
             span: span.into(),
         };
         let ansi_str = Style::new().paint("");
-        super::pretty_print(ansi_str, "", location, &file_db)
+        super::pretty_print(ansi_str, "", location, &file_db, 8)
     }
     #[test]
     fn pretty_print() {
@@ -624,4 +698,11 @@ mod test {
         );
         pp(0..0, "");
     }
+    #[test]
+    fn tab_width() {
+        let header = |s: String| s.lines().next().unwrap().to_string();
+        assert_eq!(header(pp(5..6, "\tint i;\n")), "<test-suite>:1:13 : ");
+        // the caret keeps the tab so it lines up with the source
+        assert_eq!(pp(5..6, "\tint i;\n").lines().nth(2), Some("\t    ^"));
+    }
 }